@@ -1,11 +1,47 @@
+use crate::{grid::Grid, heading};
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Algorithm {
     AStar,
     Bfs,
     Dfs,
+    SpaceTimeAStar,
+    HeadingAStar,
 }
 
 impl Algorithm {
+    pub const ALL: [Algorithm; 5] = [
+        Self::Bfs,
+        Self::Dfs,
+        Self::AStar,
+        Self::SpaceTimeAStar,
+        Self::HeadingAStar,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AStar => "A*",
+            Self::Bfs => "BFS",
+            Self::Dfs => "DFS",
+            Self::SpaceTimeAStar => "Space-time A*",
+            Self::HeadingAStar => "Heading A*",
+        }
+    }
+
+    /// Heading-aware A* moves along the eight square-grid headings, so it
+    /// only runs on single-floor square grids.
+    pub fn supports(&self, grid: &Grid) -> bool {
+        *self != Self::HeadingAStar || (!grid.topology.is_hex() && grid.floor_count() == 1)
+    }
+
+    /// A search cost in cells. Heading A* counts in tenths of a cell.
+    pub fn cost_in_cells(&self, cost: i32) -> f32 {
+        match self {
+            Self::HeadingAStar => cost as f32 / heading::STRAIGHT_COST as f32,
+            _ => cost as f32,
+        }
+    }
+
     pub fn description(&self) -> &'static str {
         match self {
             Self::AStar => {
@@ -17,6 +53,12 @@ impl Algorithm {
             Self::Dfs => {
                 "Depth-First Search (DFS) explores as far as possible along each branch before backtracking. It doesn't guarantee the optimal path but uses less memory. Uses a stack (LIFO) to maintain frontier nodes, diving deep before exploring alternatives."
             }
+            Self::HeadingAStar => {
+                "Heading-aware A* searches over (position, heading) pairs with 8-connected moves, so a differential-drive robot pays for every rotation. Each 45° or 90° turn adds its configured penalty on top of the travel distance, and A* minimises the sum. Costs are in tenths of a cell (straight 10, diagonal 14); diagonal moves may not cut wall corners."
+            }
+            Self::SpaceTimeAStar => {
+                "Space-time A* searches over (position, time) pairs so it can plan around moving obstacles. Each step either moves to a neighbour or waits in place, and a move is rejected if a moving obstacle will occupy the target cell or swap places with the robot at that time. Waiting lets the robot let an obstacle pass and thread through the gap behind it."
+            }
         }
     }
 }
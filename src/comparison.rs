@@ -0,0 +1,110 @@
+use crate::{
+    algorithms::Algorithm,
+    dynamic_obstacle::DynamicObstacle,
+    grid::Grid,
+    heading::TurnPenalties,
+    pathfinding_state::{PathfindingState, StepResult},
+    position::Position,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RunStatus {
+    Running,
+    Found,
+    NoPath,
+}
+
+/// One algorithm running on its own copy of the grid.
+pub struct ComparisonRun {
+    pub algorithm: Algorithm,
+    pub grid: Grid,
+    pub state: PathfindingState,
+    pub status: RunStatus,
+    pub peak_frontier: usize,
+    pub path: Vec<Position>,
+}
+
+impl ComparisonRun {
+    /// Cost of the found path in cells, comparable across algorithms.
+    pub fn path_cost(&self) -> Option<f32> {
+        let cost = self.path.last().and_then(|pos| self.state.g_cost(pos))?;
+        Some(self.algorithm.cost_in_cells(cost))
+    }
+}
+
+/// Runs several algorithms in lockstep on clones of the same grid.
+pub struct Comparison {
+    pub runs: Vec<ComparisonRun>,
+    start: Position,
+    goal: Position,
+}
+
+impl Comparison {
+    pub fn new(
+        grid: &Grid,
+        algorithms: &[Algorithm],
+        start: Position,
+        goal: Position,
+        obstacles: &[DynamicObstacle],
+        clearance_weight: f32,
+        turn_penalties: TurnPenalties,
+    ) -> Self {
+        let runs = algorithms
+            .iter()
+            .map(|algorithm| {
+                let mut grid = grid.clone();
+                grid.clear_pathfinding_cells();
+                let mut state = PathfindingState::new();
+                state.initialize(algorithm, grid.topology, start, goal);
+                state.set_dynamic_obstacles(obstacles);
+                state.set_clearance_weight(&grid, clearance_weight);
+                state.set_turn_penalties(turn_penalties);
+                ComparisonRun {
+                    algorithm: *algorithm,
+                    grid,
+                    peak_frontier: state.frontier_len(algorithm),
+                    state,
+                    status: RunStatus::Running,
+                    path: Vec::new(),
+                }
+            })
+            .collect();
+
+        Self { runs, start, goal }
+    }
+
+    /// Advances every unfinished run by one step.
+    /// Returns true once all runs have finished.
+    pub fn step(&mut self) -> bool {
+        for run in &mut self.runs {
+            if run.status != RunStatus::Running {
+                continue;
+            }
+            match run.state.step(&run.algorithm, self.goal, &mut run.grid) {
+                StepResult::Continue => {}
+                StepResult::PathFound(path) => {
+                    run.grid.mark_path(&path, Some(self.start), Some(self.goal));
+                    run.path = path;
+                    run.status = RunStatus::Found;
+                }
+                StepResult::NoPath => run.status = RunStatus::NoPath,
+            }
+            run.peak_frontier = run
+                .peak_frontier
+                .max(run.state.frontier_len(&run.algorithm));
+        }
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.runs.iter().all(|run| run.status != RunStatus::Running)
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn goal(&self) -> Position {
+        self.goal
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{
+    grid::{CellType, Grid},
+    position::Position,
+};
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CoveragePattern {
+    Boustrophedon,
+    Spiral,
+}
+
+impl CoveragePattern {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Boustrophedon => "Boustrophedon",
+            Self::Spiral => "Spiral",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Boustrophedon => {
+                "Boustrophedon cell decomposition splits the free space into cells wherever an obstacle splits or merges the sweep line, then covers each cell with back-and-forth (ox-plough) passes and drives between cells along shortest paths."
+            }
+            Self::Spiral => {
+                "Spiral coverage computes a wavefront of distance from the walls and always moves to the unvisited neighbour closest to the walls, so the robot circles the free space from the outside in and jumps to the nearest uncovered cell when boxed in."
+            }
+        }
+    }
+}
+
+/// A vertical run of free cells `y0..=y1` in column `x`.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: i32,
+    y0: i32,
+    y1: i32,
+}
+
+/// Plans a path from `start` that visits every free cell reachable from it.
+pub fn plan_coverage(grid: &Grid, start: Position, pattern: CoveragePattern) -> Vec<Position> {
+    let reachable = grid.reachable_cells(start);
+    match pattern {
+        CoveragePattern::Boustrophedon => plan_boustrophedon(grid, start, &reachable),
+        CoveragePattern::Spiral => plan_spiral(grid, start, &reachable),
+    }
+}
+
+/// Splits the reachable free space into boustrophedon cells: a cell keeps
+/// growing column by column while its segment overlaps exactly one segment
+/// in the next column and vice versa.
+fn decompose(grid: &Grid, reachable: &HashSet<Position>) -> Vec<Vec<Segment>> {
+    let columns: Vec<Vec<Segment>> = (0..grid.width as i32)
+        .map(|x| {
+            let mut segments = Vec::new();
+            let mut y = 0;
+            while y < grid.height as i32 {
+                if reachable.contains(&Position::new(x, y)) {
+                    let y0 = y;
+                    while reachable.contains(&Position::new(x, y + 1)) {
+                        y += 1;
+                    }
+                    segments.push(Segment { x, y0, y1: y });
+                }
+                y += 1;
+            }
+            segments
+        })
+        .collect();
+
+    let overlaps = |a: &Segment, b: &Segment| a.y0 <= b.y1 && b.y0 <= a.y1;
+    let mut cells: Vec<Vec<Segment>> = Vec::new();
+    let mut previous: Vec<(Segment, usize)> = Vec::new();
+
+    for (x, segments) in columns.iter().enumerate() {
+        let mut current = Vec::new();
+        for segment in segments {
+            let left: Vec<&(Segment, usize)> = previous
+                .iter()
+                .filter(|(p, _)| overlaps(p, segment))
+                .collect();
+            // Continue the cell only when neither side splits or merges
+            let continues = match left.as_slice() {
+                [(prev, cell)] if columns[x].iter().filter(|s| overlaps(prev, s)).count() == 1 => {
+                    Some(*cell)
+                }
+                _ => None,
+            };
+            let cell = continues.unwrap_or_else(|| {
+                cells.push(Vec::new());
+                cells.len() - 1
+            });
+            cells[cell].push(*segment);
+            current.push((*segment, cell));
+        }
+        previous = current;
+    }
+    cells
+}
+
+fn plan_boustrophedon(
+    grid: &Grid,
+    start: Position,
+    reachable: &HashSet<Position>,
+) -> Vec<Position> {
+    let mut cells = decompose(grid, reachable);
+    let mut path = vec![start];
+    let mut current = start;
+
+    while !cells.is_empty() {
+        // Visit the cell whose first column is nearest next
+        let (index, _) = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let first = cell[0];
+                let last = cell[cell.len() - 1];
+                let d_first = Position::new(first.x, first.y0).manhattan_distance_to(&current);
+                let d_last = Position::new(last.x, last.y0).manhattan_distance_to(&current);
+                (i, d_first.min(d_last))
+            })
+            .min_by_key(|(_, d)| *d)
+            .unwrap();
+        let mut cell = cells.swap_remove(index);
+
+        // Sweep from whichever end is closer
+        let first = Position::new(cell[0].x, cell[0].y0);
+        let last = Position::new(cell[cell.len() - 1].x, cell[cell.len() - 1].y0);
+        if last.manhattan_distance_to(&current) < first.manhattan_distance_to(&current) {
+            cell.reverse();
+        }
+
+        let mut downwards = {
+            let s = cell[0];
+            (current.y - s.y0).abs() <= (current.y - s.y1).abs()
+        };
+        for segment in cell {
+            let (from, to) = if downwards {
+                (segment.y0, segment.y1)
+            } else {
+                (segment.y1, segment.y0)
+            };
+            let entry = Position::new(segment.x, from);
+            extend_with_route(grid, &mut path, &mut current, entry);
+            let step = if downwards { 1 } else { -1 };
+            let mut y = from;
+            while y != to {
+                y += step;
+                current = Position::new(segment.x, y);
+                path.push(current);
+            }
+            downwards = !downwards;
+        }
+    }
+    path
+}
+
+fn plan_spiral(grid: &Grid, start: Position, reachable: &HashSet<Position>) -> Vec<Position> {
+    let clearance = wall_distance(grid, reachable);
+    let mut visited = HashSet::from([start]);
+    let mut path = vec![start];
+    let mut current = start;
+    let mut heading = Position::new(1, 0);
+
+    while visited.len() < reachable.len() {
+        let next = grid
+            .get_walkable_neighbors(&current)
+            .into_iter()
+            .filter(|n| !visited.contains(n))
+            .min_by_key(|n| {
+                let direction = Position::new(n.x - current.x, n.y - current.y);
+                // Prefer hugging the walls, then keeping the current heading
+                (clearance[n], direction != heading)
+            });
+
+        match next {
+            Some(next) => {
+                heading = Position::new(next.x - current.x, next.y - current.y);
+                current = next;
+                path.push(next);
+                visited.insert(next);
+            }
+            None => {
+                let Some(route) = route_to_nearest(grid, current, |p| !visited.contains(p)) else {
+                    break;
+                };
+                for pos in route.into_iter().skip(1) {
+                    visited.insert(pos);
+                    path.push(pos);
+                }
+                let last = path.len() - 1;
+                heading = Position::new(
+                    path[last].x - path[last - 1].x,
+                    path[last].y - path[last - 1].y,
+                );
+                current = path[last];
+            }
+        }
+    }
+    path
+}
+
+/// BFS distance of every reachable cell from the nearest wall or grid edge.
+fn wall_distance(grid: &Grid, reachable: &HashSet<Position>) -> HashMap<Position, i32> {
+    let mut distance = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &pos in reachable {
+        if pos
+            .neighbors(grid.topology)
+            .iter()
+            .any(|n| !grid.is_walkable(n))
+        {
+            distance.insert(pos, 0);
+            queue.push_back(pos);
+        }
+    }
+    while let Some(pos) = queue.pop_front() {
+        let d = distance[&pos];
+        for neighbor in grid.get_walkable_neighbors(&pos) {
+            if reachable.contains(&neighbor) && !distance.contains_key(&neighbor) {
+                distance.insert(neighbor, d + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    distance
+}
+
+/// Appends the shortest route from `current` to `target` (excluding `current`).
+fn extend_with_route(
+    grid: &Grid,
+    path: &mut Vec<Position>,
+    current: &mut Position,
+    target: Position,
+) {
+    if *current == target {
+        return;
+    }
+    if let Some(route) = route_to_nearest(grid, *current, |p| *p == target) {
+        path.extend(route.into_iter().skip(1));
+        *current = target;
+    }
+}
+
+/// BFS from `from` to the closest cell satisfying `is_target`.
+fn route_to_nearest(
+    grid: &Grid,
+    from: Position,
+    is_target: impl Fn(&Position) -> bool,
+) -> Option<Vec<Position>> {
+    let mut came_from = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(pos) = queue.pop_front() {
+        if pos != from && is_target(&pos) {
+            let mut route = vec![pos];
+            let mut current = pos;
+            while current != from {
+                current = came_from[&current];
+                route.push(current);
+            }
+            route.reverse();
+            return Some(route);
+        }
+        for neighbor in grid.get_walkable_neighbors(&pos) {
+            if let Entry::Vacant(entry) = came_from.entry(neighbor) {
+                entry.insert(pos);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    None
+}
+
+/// Animates a coverage path over the grid one cell per step.
+pub struct CoverageRun {
+    pub path: Vec<Position>,
+    index: usize,
+    visits: HashMap<Position, usize>,
+    reachable: usize,
+}
+
+impl CoverageRun {
+    pub fn new(grid: &Grid, start: Position, pattern: CoveragePattern) -> Self {
+        let reachable = grid.reachable_cells(start).len();
+        let path = plan_coverage(grid, start, pattern);
+        let visits = HashMap::from([(start, 1)]);
+        Self {
+            path,
+            index: 0,
+            visits,
+            reachable,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.index + 1 < self.path.len()
+    }
+
+    pub fn current(&self) -> Option<Position> {
+        self.path.get(self.index).copied()
+    }
+
+    /// Advances the sweep by one cell, colouring covered cells as `Path`
+    /// and cells driven over again as `Visited`.
+    pub fn step(&mut self, grid: &mut Grid) {
+        if !self.is_running() {
+            return;
+        }
+        if let Some(previous) = self.current() {
+            mark_swept(grid, previous, self.visits[&previous]);
+        }
+        self.index += 1;
+        let pos = self.path[self.index];
+        *self.visits.entry(pos).or_insert(0) += 1;
+        if matches!(
+            grid.get_cell(&pos),
+            CellType::Empty | CellType::Path | CellType::Visited
+        ) {
+            grid.mark_current(pos);
+        }
+    }
+
+    pub fn finish(&mut self, grid: &mut Grid) {
+        while self.is_running() {
+            self.step(grid);
+        }
+    }
+
+    pub fn coverage_percent(&self) -> f32 {
+        if self.reachable == 0 {
+            return 0.0;
+        }
+        100.0 * self.visits.len() as f32 / self.reachable as f32
+    }
+
+    /// Number of cells driven over more than once so far.
+    pub fn overlap_cells(&self) -> usize {
+        self.visits.values().filter(|&&count| count > 1).count()
+    }
+
+    pub fn steps_taken(&self) -> usize {
+        self.index
+    }
+
+    pub fn total_steps(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+}
+
+fn mark_swept(grid: &mut Grid, pos: Position, visits: usize) {
+    if matches!(grid.get_cell(&pos), CellType::Start | CellType::Goal) {
+        return;
+    }
+    let cell = if visits > 1 {
+        CellType::Visited
+    } else {
+        CellType::Path
+    };
+    grid.set_cell(pos, cell);
+}
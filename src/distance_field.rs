@@ -0,0 +1,256 @@
+use crate::{
+    grid::{CellType, Grid},
+    position::Position,
+    topology::Topology,
+};
+use std::collections::VecDeque;
+
+/// Cells closer than this to an obstacle pay the clearance penalty.
+pub const CLEARANCE_RANGE: f32 = 4.0;
+
+/// Obstacle distance fields per floor, kept until the grid's walls change.
+/// The transform is too slow to rerun every frame on large grids.
+#[derive(Default)]
+pub struct DistanceCache {
+    key: Option<(u64, Topology)>,
+    // Field and its largest finite value, per floor
+    floors: Vec<Option<(Vec<Vec<f32>>, f32)>>,
+}
+
+impl DistanceCache {
+    /// The distance field of `floor` and its largest finite value.
+    pub fn get(&mut self, grid: &Grid, floor: i32) -> (&[Vec<f32>], f32) {
+        let key = Some((grid.revision(), grid.topology));
+        if self.key != key {
+            self.key = key;
+            self.floors = vec![None; grid.floor_count()];
+        }
+        let (field, max) = self.floors[floor as usize].get_or_insert_with(|| {
+            let field = obstacle_distance(grid, floor);
+            let max = max_distance(&field);
+            (field, max)
+        });
+        (field, *max)
+    }
+
+    /// Largest finite distance on `floor`, if that field is cached and
+    /// still current.
+    pub fn cached_max(&self, grid: &Grid, floor: i32) -> Option<f32> {
+        if self.key != Some((grid.revision(), grid.topology)) {
+            return None;
+        }
+        self.floors
+            .get(floor as usize)?
+            .as_ref()
+            .map(|(_, max)| *max)
+    }
+}
+
+/// Euclidean distance from every cell of `floor` to the nearest obstacle on
+/// that floor, in cells.
+pub fn obstacle_distance(grid: &Grid, floor: i32) -> Vec<Vec<f32>> {
+    squared_obstacle_distance(grid, floor)
+        .into_iter()
+        .map(|row| row.into_iter().map(f32::sqrt).collect())
+        .collect()
+}
+
+/// Largest finite distance in the field, at least 1 so it can divide.
+pub fn max_distance(field: &[Vec<f32>]) -> f32 {
+    field
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|d| d.is_finite())
+        .fold(1.0, f32::max)
+}
+
+/// Extra cost of entering each cell, growing linearly from 0 at
+/// `CLEARANCE_RANGE` to `weight * CLEARANCE_RANGE` next to a wall.
+/// Indexed `[floor][y][x]`.
+pub fn clearance_penalty(grid: &Grid, weight: f32) -> Vec<Vec<Vec<i32>>> {
+    (0..grid.floor_count() as i32)
+        .map(|floor| {
+            obstacle_distance(grid, floor)
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|d| (weight * (CLEARANCE_RANGE - d).max(0.0)).round() as i32)
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Exact squared Euclidean distance from every cell centre of `floor` to the
+/// nearest obstacle cell centre on that floor, indexed `[y][x]`. Computed
+/// separably with the Felzenszwalb–Huttenlocher lower-envelope transform,
+/// first down each column and then along each row. Without any obstacles
+/// every cell is `f32::INFINITY`. On hex grids the axial indices are
+/// sheared, so the squared hex step count is used instead.
+pub fn squared_obstacle_distance(grid: &Grid, floor: i32) -> Vec<Vec<f32>> {
+    if grid.topology.is_hex() {
+        return squared_step_distance(grid, floor);
+    }
+    let mut distance: Vec<Vec<f32>> = (0..grid.height as i32)
+        .map(|y| {
+            (0..grid.width as i32)
+                .map(|x| {
+                    if grid.get_cell(&Position::on_floor(x, y, floor)) == CellType::Obstacle {
+                        0.0
+                    } else {
+                        f32::INFINITY
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut column = vec![0.0; grid.height];
+    for x in 0..grid.width {
+        for (y, row) in distance.iter().enumerate() {
+            column[y] = row[x];
+        }
+        let transformed = transform_1d(&column);
+        for (y, row) in distance.iter_mut().enumerate() {
+            row[x] = transformed[y];
+        }
+    }
+    for row in &mut distance {
+        *row = transform_1d(row);
+    }
+    distance
+}
+
+/// Multi-source breadth-first search from every obstacle cell over the
+/// grid's neighbours, ignoring walkability, with the step counts squared.
+fn squared_step_distance(grid: &Grid, floor: i32) -> Vec<Vec<f32>> {
+    let mut distance = vec![vec![f32::INFINITY; grid.width]; grid.height];
+    let mut queue = VecDeque::new();
+    for y in 0..grid.height as i32 {
+        for x in 0..grid.width as i32 {
+            let pos = Position::on_floor(x, y, floor);
+            if grid.get_cell(&pos) == CellType::Obstacle {
+                distance[y as usize][x as usize] = 0.0;
+                queue.push_back((pos, 0.0));
+            }
+        }
+    }
+    while let Some((pos, steps)) = queue.pop_front() {
+        for next in pos.neighbors(grid.topology) {
+            if !grid.is_valid_position(&next) {
+                continue;
+            }
+            let slot = &mut distance[next.y as usize][next.x as usize];
+            if slot.is_infinite() {
+                *slot = steps + 1.0;
+                queue.push_back((next, steps + 1.0));
+            }
+        }
+    }
+    for d in distance.iter_mut().flatten() {
+        *d *= *d;
+    }
+    distance
+}
+
+/// One-dimensional squared distance transform of the sampled function `f`:
+/// `d[q] = min_p (q - p)² + f[p]`.
+fn transform_1d(f: &[f32]) -> Vec<f32> {
+    let n = f.len();
+    let mut result = vec![f32::INFINITY; n];
+    // Parabola apexes forming the lower envelope and the boundaries between them
+    let mut apex = vec![0usize; n];
+    let mut boundary = vec![0.0f32; n + 1];
+    let mut k = 0;
+
+    let Some(first) = f.iter().position(|v| v.is_finite()) else {
+        return result;
+    };
+    apex[0] = first;
+    boundary[0] = f32::NEG_INFINITY;
+    boundary[1] = f32::INFINITY;
+
+    let intersection = |q: usize, p: usize| {
+        let (qf, pf) = (q as f32, p as f32);
+        ((f[q] + qf * qf) - (f[p] + pf * pf)) / (2.0 * qf - 2.0 * pf)
+    };
+    for (q, value) in f.iter().enumerate().skip(first + 1) {
+        if !value.is_finite() {
+            continue;
+        }
+        let mut s = intersection(q, apex[k]);
+        while s <= boundary[k] {
+            k -= 1;
+            s = intersection(q, apex[k]);
+        }
+        k += 1;
+        apex[k] = q;
+        boundary[k] = s;
+        boundary[k + 1] = f32::INFINITY;
+    }
+
+    k = 0;
+    for (q, value) in result.iter_mut().enumerate() {
+        while boundary[k + 1] < q as f32 {
+            k += 1;
+        }
+        let d = q as f32 - apex[k] as f32;
+        *value = d * d + f[apex[k]];
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Squared distance to the nearest obstacle by checking every pair.
+    fn brute_force(grid: &Grid) -> Vec<Vec<f32>> {
+        let obstacles: Vec<(i32, i32)> = (0..grid.height as i32)
+            .flat_map(|y| (0..grid.width as i32).map(move |x| (x, y)))
+            .filter(|&(x, y)| grid.get_cell(&Position::new(x, y)) == CellType::Obstacle)
+            .collect();
+        (0..grid.height as i32)
+            .map(|y| {
+                (0..grid.width as i32)
+                    .map(|x| {
+                        obstacles
+                            .iter()
+                            .map(|&(ox, oy)| ((x - ox).pow(2) + (y - oy).pow(2)) as f32)
+                            .fold(f32::INFINITY, f32::min)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transform_matches_brute_force() {
+        let mut grid = Grid::new(13, 9, 10.0);
+        for (x, y) in [(0, 0), (12, 8), (6, 4), (3, 7), (10, 1), (7, 5)] {
+            grid.set_cell(Position::new(x, y), CellType::Obstacle);
+        }
+        assert_eq!(squared_obstacle_distance(&grid, 0), brute_force(&grid));
+    }
+
+    #[test]
+    fn no_obstacles_is_infinite_everywhere() {
+        let grid = Grid::new(4, 3, 10.0);
+        let field = squared_obstacle_distance(&grid, 0);
+        assert!(field.iter().flatten().all(|d| d.is_infinite()));
+        assert_eq!(max_distance(&obstacle_distance(&grid, 0)), 1.0);
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_wall_edits() {
+        let mut grid = Grid::new(5, 1, 10.0);
+        grid.set_cell(Position::new(0, 0), CellType::Obstacle);
+        let mut cache = DistanceCache::default();
+        assert_eq!(cache.get(&grid, 0).1, 4.0);
+        grid.set_cell(Position::new(4, 0), CellType::Obstacle);
+        assert_eq!(cache.cached_max(&grid, 0), None);
+        assert_eq!(cache.get(&grid, 0).1, 2.0);
+    }
+}
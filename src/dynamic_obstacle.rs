@@ -0,0 +1,121 @@
+use crate::position::Position;
+
+/// An obstacle that moves one cell per time step along a looping route.
+#[derive(Clone, Debug)]
+pub struct DynamicObstacle {
+    route: Vec<Position>,
+}
+
+impl DynamicObstacle {
+    /// Builds a route through `waypoints` (straight horizontal, then vertical
+    /// moves between them) that drives back along itself, so it loops
+    /// without jumping.
+    pub fn from_waypoints(waypoints: &[Position]) -> Option<Self> {
+        let first = *waypoints.first()?;
+        let mut forward = vec![first];
+        for &target in &waypoints[1..] {
+            let mut current = *forward.last().unwrap();
+            while current.x != target.x {
+                current.x += (target.x - current.x).signum();
+                forward.push(current);
+            }
+            while current.y != target.y {
+                current.y += (target.y - current.y).signum();
+                forward.push(current);
+            }
+        }
+
+        let mut route = forward.clone();
+        if forward.len() > 1 {
+            route.extend(forward.iter().rev().skip(1).take(forward.len() - 2));
+        }
+        Some(Self { route })
+    }
+
+    /// Number of steps before the obstacle is back where it started.
+    pub fn period(&self) -> usize {
+        self.route.len()
+    }
+
+    pub fn position_at(&self, t: usize) -> Position {
+        self.route[t % self.route.len()]
+    }
+
+    /// Position at a fractional time, sliding between cells.
+    pub fn interpolated_at(&self, t: f32) -> (f32, f32) {
+        let step = t.max(0.0).floor() as usize;
+        let frac = t.max(0.0) - step as f32;
+        let (a, b) = (self.position_at(step), self.position_at(step + 1));
+        (
+            a.x as f32 + (b.x - a.x) as f32 * frac,
+            a.y as f32 + (b.y - a.y) as f32 * frac,
+        )
+    }
+
+    pub fn shifted(&self, offset: Position) -> Self {
+        let route = self
+            .route
+            .iter()
+            .map(|p| Position::new(p.x + offset.x, p.y + offset.y))
+            .collect();
+        Self { route }
+    }
+
+    /// The forward half of the route, for drawing.
+    pub fn route(&self) -> &[Position] {
+        &self.route[..self.route.len() / 2 + 1]
+    }
+}
+
+/// True if moving from `from` at time `t` to `to` at `t + 1` hits any
+/// obstacle, either by sharing the target cell or by swapping places.
+pub fn collides(obstacles: &[DynamicObstacle], from: Position, to: Position, t: usize) -> bool {
+    obstacles.iter().any(|obstacle| {
+        let now = obstacle.position_at(t);
+        let next = obstacle.position_at(t + 1);
+        next == to || (now == to && next == from)
+    })
+}
+
+/// Smallest time after which every obstacle is back at its starting cell,
+/// saturated at `cap`: a few long co-prime routes overflow the LCM.
+pub fn combined_period(obstacles: &[DynamicObstacle], cap: usize) -> usize {
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+    obstacles
+        .iter()
+        .map(DynamicObstacle::period)
+        .try_fold(1usize, |acc, p| {
+            (acc / gcd(acc, p)).checked_mul(p).filter(|&lcm| lcm < cap)
+        })
+        .unwrap_or(cap)
+        .min(cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Back-and-forth along a row: period 2 × `length`.
+    fn shuttle(length: i32) -> DynamicObstacle {
+        DynamicObstacle::from_waypoints(&[Position::new(0, 0), Position::new(length, 0)]).unwrap()
+    }
+
+    #[test]
+    fn combined_period_is_the_lcm() {
+        let obstacles = [shuttle(2), shuttle(3)];
+        assert_eq!(combined_period(&obstacles, 1000), 12);
+    }
+
+    #[test]
+    fn combined_period_saturates_instead_of_overflowing() {
+        // Co-prime periods whose LCM is far beyond usize::MAX
+        let obstacles: Vec<_> = [1009, 1013, 1019, 1021, 1031, 1033, 1039]
+            .into_iter()
+            .map(shuttle)
+            .collect();
+        assert_eq!(combined_period(&obstacles, 5000), 5000);
+        assert_eq!(combined_period(&obstacles, usize::MAX), usize::MAX);
+    }
+}
@@ -0,0 +1,476 @@
+use crate::{
+    algorithms::Algorithm,
+    grid::{CellType, Grid},
+    pathfinding_state::PathfindingState,
+    position::Position,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// What the planner assumes about cells the robot has not sensed yet.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnknownPolicy {
+    AssumeFree,
+    AssumeBlocked,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SimStatus {
+    Running,
+    Reached,
+    Stuck,
+}
+
+/// Which cells of the true grid the robot has observed so far.
+#[derive(Clone)]
+pub struct BeliefMap {
+    known: Vec<bool>,
+    width: usize,
+    height: usize,
+    known_count: usize,
+}
+
+impl BeliefMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            known: vec![false; width * height],
+            width,
+            height,
+            known_count: 0,
+        }
+    }
+
+    fn index(&self, pos: &Position) -> Option<usize> {
+        (pos.x >= 0 && pos.x < self.width as i32 && pos.y >= 0 && pos.y < self.height as i32)
+            .then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    pub fn is_known(&self, pos: &Position) -> bool {
+        self.index(pos).is_some_and(|i| self.known[i])
+    }
+
+    pub fn known_count(&self) -> usize {
+        self.known_count
+    }
+
+    pub fn total_cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Reveals every cell within `radius` of `center` that is in line of sight.
+    /// Returns the newly revealed positions.
+    pub fn sense(&mut self, grid: &Grid, center: Position, radius: i32) -> Vec<Position> {
+        let mut revealed = Vec::new();
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if !grid.topology.within(dx, dy, radius as f32) {
+                    continue;
+                }
+                let pos = Position::new(center.x + dx, center.y + dy);
+                let Some(index) = self.index(&pos) else {
+                    continue;
+                };
+                if self.known[index] || !has_line_of_sight(grid, center, pos) {
+                    continue;
+                }
+                self.known[index] = true;
+                self.known_count += 1;
+                revealed.push(pos);
+            }
+        }
+        revealed
+    }
+
+    /// Builds the grid the robot believes in: known cells copied from the
+    /// true grid, unknown cells filled according to `policy`.
+    pub fn belief_grid(&self, grid: &Grid, policy: UnknownPolicy) -> Grid {
+        let mut belief = Grid::new(self.width, self.height, grid.size);
+        belief.topology = grid.topology;
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let pos = Position::new(x, y);
+                let cell = if self.is_known(&pos) {
+                    grid.get_cell(&pos)
+                } else {
+                    match policy {
+                        UnknownPolicy::AssumeFree => CellType::Empty,
+                        UnknownPolicy::AssumeBlocked => CellType::Obstacle,
+                    }
+                };
+                if cell == CellType::Obstacle {
+                    belief.set_cell(pos, CellType::Obstacle);
+                }
+            }
+        }
+        belief.set_robot_radius(grid.robot_radius());
+        belief
+    }
+}
+
+/// Bresenham line from `from` to `to`; only the end cell may be an obstacle.
+pub fn has_line_of_sight(grid: &Grid, from: Position, to: Position) -> bool {
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let (mut x, mut y, mut err) = (from.x, from.y, dx + dy);
+    while (x, y) != (to.x, to.y) {
+        // Sensing sees past the footprint inflation; only real walls block it
+        if (x, y) != (from.x, from.y) && grid.get_cell(&Position::new(x, y)) == CellType::Obstacle {
+            return false;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+    true
+}
+
+/// Drives a robot towards the goal on a partially known map, replanning
+/// whenever sensing invalidates the current plan.
+pub struct SensorSim {
+    pub belief: BeliefMap,
+    pub robot: Position,
+    pub goal: Position,
+    pub plan: Vec<Position>,
+    pub trail: Vec<Position>,
+    pub replans: usize,
+    pub optimum: Option<usize>,
+    pub status: SimStatus,
+    sensor_radius: i32,
+    policy: UnknownPolicy,
+}
+
+impl SensorSim {
+    pub fn new(
+        grid: &Grid,
+        start: Position,
+        goal: Position,
+        sensor_radius: i32,
+        policy: UnknownPolicy,
+    ) -> Self {
+        let optimum =
+            PathfindingState::solve(&Algorithm::AStar, start, goal, &[], &mut grid.clone())
+                .map(|path| path.len() - 1);
+        let mut belief = BeliefMap::new(grid.width, grid.height);
+        belief.sense(grid, start, sensor_radius);
+
+        let mut sim = Self {
+            belief,
+            robot: start,
+            goal,
+            plan: Vec::new(),
+            trail: vec![start],
+            replans: 0,
+            optimum,
+            status: SimStatus::Running,
+            sensor_radius,
+            policy,
+        };
+        sim.replan(grid);
+        sim
+    }
+
+    pub fn distance_travelled(&self) -> usize {
+        self.trail.len() - 1
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == SimStatus::Running
+    }
+
+    /// Moves the robot one cell along its plan, senses, and replans if needed.
+    pub fn step(&mut self, grid: &Grid) {
+        if !self.is_running() {
+            return;
+        }
+        if self.plan.is_empty() {
+            self.status = SimStatus::Stuck;
+            return;
+        }
+
+        self.robot = self.plan.remove(0);
+        self.trail.push(self.robot);
+        if self.robot == self.goal {
+            self.status = SimStatus::Reached;
+            return;
+        }
+
+        let revealed = self.belief.sense(grid, self.robot, self.sensor_radius);
+        let blocked = self
+            .plan
+            .iter()
+            .any(|pos| !grid.is_walkable(pos) && self.belief.is_known(pos));
+        let opened = self.policy == UnknownPolicy::AssumeBlocked && !revealed.is_empty();
+        if blocked || opened || self.plan.is_empty() {
+            self.replan(grid);
+            self.replans += 1;
+        }
+    }
+
+    fn replan(&mut self, grid: &Grid) {
+        let mut belief = self.belief.belief_grid(grid, self.policy);
+        // Aim for an unsensed goal even when unknown cells count as blocked,
+        // but never drive into one the robot has seen is blocked
+        if !self.belief.is_known(&self.goal) {
+            belief.set_cell(self.goal, CellType::Empty);
+        }
+
+        let target = match self.policy {
+            UnknownPolicy::AssumeFree => Some(self.goal),
+            // With unknown cells blocked the goal is usually unreachable, so
+            // head for the most promising frontier and sense from there.
+            UnknownPolicy::AssumeBlocked => self.best_frontier(&belief),
+        };
+
+        self.plan = target
+            .and_then(|target| {
+                PathfindingState::solve(&Algorithm::AStar, self.robot, target, &[], &mut belief)
+            })
+            .map(|path| path.into_iter().skip(1).collect())
+            .unwrap_or_default();
+        if self.plan.is_empty() {
+            self.status = SimStatus::Stuck;
+        }
+    }
+
+    /// Picks the reachable frontier cell (known cell next to unknown space)
+    /// that minimises travel distance plus the estimated remaining distance
+    /// to the goal. Returns the goal itself once it is reachable.
+    fn best_frontier(&self, belief: &Grid) -> Option<Position> {
+        let mut distance = HashMap::from([(self.robot, 0)]);
+        let mut queue = VecDeque::from([self.robot]);
+        let mut best: Option<(i32, Position)> = None;
+        while let Some(pos) = queue.pop_front() {
+            if pos == self.goal {
+                return Some(pos);
+            }
+            let is_frontier = pos
+                .neighbors(belief.topology)
+                .iter()
+                .any(|n| belief.is_valid_position(n) && !self.belief.is_known(n));
+            if pos != self.robot && is_frontier {
+                let cost = distance[&pos] + belief.distance(&pos, &self.goal);
+                if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    best = Some((cost, pos));
+                }
+            }
+            for neighbor in belief.get_walkable_neighbors(&pos) {
+                if !distance.contains_key(&neighbor) {
+                    distance.insert(neighbor, distance[&pos] + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        best.map(|(_, pos)| pos)
+    }
+}
+
+/// Autonomous exploration: repeatedly drive to the nearest frontier cell
+/// (known free cell next to unknown space) until none are reachable.
+pub struct FrontierExplorer {
+    pub belief: BeliefMap,
+    pub robot: Position,
+    pub plan: Vec<Position>,
+    pub trail: Vec<Position>,
+    pub target: Option<Position>,
+    pub frontiers_visited: usize,
+    pub complete: bool,
+    // Cells the robot can ever see: reachable free cells and their
+    // neighbours, so sealed-off areas and wall interiors don't count
+    observable: HashSet<Position>,
+    observed: usize,
+    // Percentage of the map revealed after each step
+    pub coverage_history: Vec<f32>,
+    sensor_radius: i32,
+}
+
+impl FrontierExplorer {
+    pub fn new(grid: &Grid, start: Position, sensor_radius: i32) -> Self {
+        let mut explorer = Self {
+            belief: BeliefMap::new(grid.width, grid.height),
+            robot: start,
+            plan: Vec::new(),
+            trail: vec![start],
+            target: None,
+            frontiers_visited: 0,
+            complete: false,
+            observable: observable_cells(grid, start),
+            observed: 0,
+            coverage_history: Vec::new(),
+            sensor_radius,
+        };
+        explorer.sense(grid);
+        explorer.select_frontier(grid);
+        explorer
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.complete
+    }
+
+    /// Share of the observable cells seen so far; 100% once exploration
+    /// completes.
+    pub fn explored_percent(&self) -> f32 {
+        100.0 * self.observed as f32 / self.observable.len().max(1) as f32
+    }
+
+    pub fn distance_travelled(&self) -> usize {
+        self.trail.len() - 1
+    }
+
+    /// Senses from the robot's cell and records the explored share.
+    fn sense(&mut self, grid: &Grid) {
+        let revealed = self.belief.sense(grid, self.robot, self.sensor_radius);
+        self.observed += revealed
+            .iter()
+            .filter(|pos| self.observable.contains(pos))
+            .count();
+        self.coverage_history.push(self.explored_percent());
+    }
+
+    /// A known, walkable cell with at least one unknown neighbour.
+    pub fn is_frontier(&self, grid: &Grid, pos: &Position) -> bool {
+        self.belief.is_known(pos)
+            && grid.is_walkable(pos)
+            && pos
+                .neighbors(grid.topology)
+                .iter()
+                .any(|n| grid.is_valid_position(n) && !self.belief.is_known(n))
+    }
+
+    pub fn step(&mut self, grid: &Grid) {
+        if self.complete {
+            return;
+        }
+
+        if let Some(&next) = self.plan.first() {
+            if grid.is_walkable(&next) {
+                self.plan.remove(0);
+                self.robot = next;
+                self.trail.push(next);
+            } else {
+                self.plan.clear();
+            }
+        }
+
+        self.sense(grid);
+
+        let target_done = self
+            .target
+            .is_none_or(|target| self.robot == target || !self.is_frontier(grid, &target));
+        let blocked = self
+            .plan
+            .iter()
+            .any(|pos| self.belief.is_known(pos) && !grid.is_walkable(pos));
+        if target_done || blocked || self.plan.is_empty() {
+            if target_done && self.target.is_some() {
+                self.frontiers_visited += 1;
+            }
+            self.select_frontier(grid);
+        }
+    }
+
+    /// BFS over known free cells; the first frontier reached is the cheapest.
+    fn select_frontier(&mut self, grid: &Grid) {
+        let mut came_from = HashMap::new();
+        let mut queue = VecDeque::from([self.robot]);
+        came_from.insert(self.robot, self.robot);
+
+        while let Some(pos) = queue.pop_front() {
+            if pos != self.robot && self.is_frontier(grid, &pos) {
+                let mut path = vec![pos];
+                let mut current = pos;
+                while let Some(&parent) = came_from.get(&current) {
+                    if parent == current || parent == self.robot {
+                        break;
+                    }
+                    path.push(parent);
+                    current = parent;
+                }
+                path.reverse();
+                self.target = Some(pos);
+                self.plan = path;
+                return;
+            }
+            for neighbor in grid.get_walkable_neighbors(&pos) {
+                if self.belief.is_known(&neighbor) && !came_from.contains_key(&neighbor) {
+                    came_from.insert(neighbor, pos);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        self.target = None;
+        self.plan.clear();
+        self.complete = true;
+    }
+}
+
+/// Free cells reachable from `start` plus their neighbours. Frontier
+/// exploration ends once all of them are known.
+fn observable_cells(grid: &Grid, start: Position) -> HashSet<Position> {
+    let reachable = grid.reachable_cells(start);
+    let mut observable = reachable.clone();
+    for pos in &reachable {
+        observable.extend(
+            pos.neighbors(grid.topology)
+                .into_iter()
+                .filter(|n| grid.is_valid_position(n)),
+        );
+    }
+    observable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(sim: &mut SensorSim, grid: &Grid) {
+        for _ in 0..1000 {
+            if !sim.is_running() {
+                break;
+            }
+            sim.step(grid);
+        }
+    }
+
+    #[test]
+    fn never_enters_a_goal_known_to_be_blocked() {
+        let mut grid = Grid::new(8, 3, 10.0);
+        grid.set_cell(Position::new(6, 1), CellType::Obstacle);
+        for policy in [UnknownPolicy::AssumeFree, UnknownPolicy::AssumeBlocked] {
+            let mut sim =
+                SensorSim::new(&grid, Position::new(0, 1), Position::new(6, 1), 3, policy);
+            run(&mut sim, &grid);
+            assert_eq!(sim.status, SimStatus::Stuck, "{policy:?}");
+            assert!(!sim.trail.contains(&Position::new(6, 1)));
+        }
+    }
+
+    #[test]
+    fn escapes_a_dead_end_facing_the_goal() {
+        // A pocket opening left, pointing at a goal behind its back wall
+        let mut grid = Grid::new(12, 7, 10.0);
+        for x in 3..8 {
+            grid.set_cell(Position::new(x, 2), CellType::Obstacle);
+            grid.set_cell(Position::new(x, 4), CellType::Obstacle);
+        }
+        for y in 0..7 {
+            grid.set_cell(Position::new(8, y), CellType::Obstacle);
+        }
+        grid.set_cell(Position::new(8, 0), CellType::Empty);
+        let mut sim = SensorSim::new(
+            &grid,
+            Position::new(4, 3),
+            Position::new(10, 3),
+            2,
+            UnknownPolicy::AssumeBlocked,
+        );
+        run(&mut sim, &grid);
+        assert_eq!(sim.status, SimStatus::Reached);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    grid::Grid,
+    heading::{DIAGONAL_COST, STRAIGHT_COST},
+    position::Position,
+    smoothing::cell_center,
+    topology::Topology,
+};
+use egui::Vec2;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Distance to the goal from every cell (one Dijkstra wavefront over the
+/// grid's neighbours, plus diagonals without corner cutting on squares) and
+/// the neighbour each cell points to downhill.
+pub struct FlowField {
+    pub goal: Position,
+    pub topology: Topology,
+    // Travel cost in tenths of a cell; `None` where the goal is unreachable
+    distance: Vec<Vec<Option<i32>>>,
+    next: Vec<Vec<Option<Position>>>,
+}
+
+impl FlowField {
+    pub fn compute(grid: &Grid, goal: Position) -> Self {
+        let mut distance = vec![vec![None; grid.width]; grid.height];
+        let mut next = vec![vec![None; grid.width]; grid.height];
+        if !grid.is_walkable(&goal) {
+            return Self {
+                goal,
+                topology: grid.topology,
+                distance,
+                next,
+            };
+        }
+
+        distance[goal.y as usize][goal.x as usize] = Some(0);
+        let mut open = BinaryHeap::from([Reverse((0, goal.x, goal.y))]);
+        while let Some(Reverse((d, x, y))) = open.pop() {
+            let pos = Position::new(x, y);
+            if distance[y as usize][x as usize].is_some_and(|best| best < d) {
+                continue;
+            }
+            for (neighbor, cost) in moves(grid, &pos) {
+                let nd = d + cost;
+                let slot = &mut distance[neighbor.y as usize][neighbor.x as usize];
+                if slot.is_none_or(|best| nd < best) {
+                    *slot = Some(nd);
+                    open.push(Reverse((nd, neighbor.x, neighbor.y)));
+                }
+            }
+        }
+
+        // Each cell points at the neighbour that is closest to the goal
+        for y in 0..grid.height as i32 {
+            for x in 0..grid.width as i32 {
+                let pos = Position::new(x, y);
+                if pos == goal || distance[y as usize][x as usize].is_none() {
+                    continue;
+                }
+                next[y as usize][x as usize] = moves(grid, &pos)
+                    .into_iter()
+                    .filter_map(|(neighbor, _)| {
+                        distance[neighbor.y as usize][neighbor.x as usize].map(|d| (d, neighbor))
+                    })
+                    .min_by_key(|(d, _)| *d)
+                    .map(|(_, neighbor)| neighbor);
+            }
+        }
+
+        Self {
+            goal,
+            topology: grid.topology,
+            distance,
+            next,
+        }
+    }
+
+    pub fn distance(&self, pos: &Position) -> Option<i32> {
+        self.distance
+            .get(pos.y as usize)
+            .and_then(|row| row.get(pos.x as usize))
+            .copied()
+            .flatten()
+    }
+
+    /// The neighbour `pos` flows into.
+    pub fn next_cell(&self, pos: &Position) -> Option<Position> {
+        self.next
+            .get(pos.y as usize)
+            .and_then(|row| row.get(pos.x as usize))
+            .copied()
+            .flatten()
+    }
+
+    pub fn max_distance(&self) -> i32 {
+        self.distance
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn reachable_count(&self) -> usize {
+        self.distance.iter().flatten().flatten().count()
+    }
+}
+
+/// The grid's walkable neighbours on the same floor at straight cost, plus
+/// diagonals on square grids when both side cells are free.
+fn moves(grid: &Grid, pos: &Position) -> Vec<(Position, i32)> {
+    let mut moves: Vec<(Position, i32)> = grid
+        .get_walkable_neighbors(pos)
+        .into_iter()
+        .filter(|next| next.floor == pos.floor)
+        .map(|next| (next, STRAIGHT_COST))
+        .collect();
+    if !grid.topology.is_hex() {
+        for (dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)] {
+            let next = Position::new(pos.x + dx, pos.y + dy);
+            let clear = grid.is_walkable(&next)
+                && grid.is_walkable(&Position::new(pos.x + dx, pos.y))
+                && grid.is_walkable(&Position::new(pos.x, pos.y + dy));
+            if clear {
+                moves.push((next, DIAGONAL_COST));
+            }
+        }
+    }
+    moves
+}
+
+/// An agent that steers towards the centre of whichever cell the field
+/// points to from its current cell.
+#[derive(Clone, Copy, Debug)]
+pub struct FlowAgent {
+    pub position: Vec2,
+    pub arrived: bool,
+}
+
+impl FlowAgent {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            arrived: false,
+        }
+    }
+
+    pub fn cell(&self, topology: Topology) -> Position {
+        let (x, y) = topology.cell_at(self.position);
+        Position::new(x, y)
+    }
+
+    /// True if the agent stands where the field has no way to the goal.
+    pub fn is_stranded(&self, field: &FlowField) -> bool {
+        field.distance(&self.cell(field.topology)).is_none()
+    }
+
+    /// Moves up to `distance` cells along the field.
+    pub fn advance(&mut self, field: &FlowField, distance: f32) {
+        if self.arrived {
+            return;
+        }
+        let cell = self.cell(field.topology);
+        let target = if cell == field.goal {
+            cell_center(&field.goal)
+        } else if let Some(next) = field.next_cell(&cell) {
+            cell_center(&next)
+        } else {
+            return;
+        };
+
+        // Speed is measured where neighbouring centres are one unit apart
+        let offset = target - self.position;
+        let length = field.topology.layout_of(offset).length();
+        if length <= distance {
+            self.position = target;
+            self.arrived = cell == field.goal;
+        } else {
+            self.position += offset * (distance / length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithms::Algorithm, grid::CellType, pathfinding_state::PathfindingState};
+
+    fn walled(topology: Topology) -> Grid {
+        let mut grid = Grid::new(9, 7, 10.0);
+        grid.topology = topology;
+        for y in 0..5 {
+            grid.set_cell(Position::new(4, y), CellType::Obstacle);
+        }
+        grid
+    }
+
+    #[test]
+    fn hex_field_follows_neighbours_downhill_to_the_goal() {
+        let grid = walled(Topology::PointyHex);
+        let goal = Position::new(7, 1);
+        let field = FlowField::compute(&grid, goal);
+        let path = PathfindingState::solve(
+            &Algorithm::Bfs,
+            Position::new(1, 1),
+            goal,
+            &[],
+            &mut grid.clone(),
+        )
+        .unwrap();
+        let steps = path.len() as i32 - 1;
+        assert_eq!(
+            field.distance(&Position::new(1, 1)),
+            Some(steps * STRAIGHT_COST)
+        );
+
+        for pos in grid.reachable_cells(goal) {
+            let mut cell = pos;
+            while cell != goal {
+                let next = field.next_cell(&cell).unwrap();
+                assert!(cell.neighbors(grid.topology).contains(&next));
+                assert!(field.distance(&next) < field.distance(&cell));
+                cell = next;
+            }
+        }
+    }
+
+    #[test]
+    fn square_field_moves_diagonally_without_cutting_corners() {
+        let grid = walled(Topology::Square);
+        let field = FlowField::compute(&grid, Position::new(8, 6));
+        assert_eq!(
+            field.distance(&Position::new(6, 4)),
+            Some(2 * DIAGONAL_COST)
+        );
+        // Past the wall's end at (4, 4) the flow may not cut its corner
+        assert_eq!(
+            field.next_cell(&Position::new(3, 4)),
+            Some(Position::new(3, 5))
+        );
+    }
+
+    #[test]
+    fn hex_agent_arrives() {
+        let grid = walled(Topology::FlatHex);
+        let field = FlowField::compute(&grid, Position::new(7, 1));
+        let mut agent = FlowAgent::new(cell_center(&Position::new(1, 2)));
+        for _ in 0..200 {
+            agent.advance(&field, 0.25);
+        }
+        assert!(agent.arrived);
+    }
+}
@@ -1,7 +1,9 @@
-use crate::position::Position;
-use serde::{Deserialize, Serialize, Serializer};
+use crate::{distance_field, position::Position, topology::Topology};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use egui::Color32;
+use egui::{Color32, Vec2};
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Copy)]
 pub enum CellType {
@@ -45,48 +47,513 @@ impl Serialize for CellType {
     }
 }
 
+/// Which part of the existing content stays fixed when the grid is resized.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResizeAnchor {
+    TopLeft,
+    Center,
+}
+
+/// A cell linking floors. Stairs lead to the floor directly above or below
+/// where the same cell is also stairs; an elevator stops at every floor
+/// where its shaft has a door at the same cell.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Connector {
+    Stairs,
+    Elevator,
+}
+
+impl Connector {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Stairs => "Stairs",
+            Self::Elevator => "Elevator",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Stairs => "S",
+            Self::Elevator => "E",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            Self::Stairs => Color32::from_rgb(196, 140, 82),
+            Self::Elevator => Color32::from_rgb(120, 132, 196),
+        }
+    }
+}
+
 #[derive(Deserialize, Clone, Serialize)]
 pub struct Grid {
-    cells: Vec<Vec<CellType>>,
+    // One layer of cells per floor, indexed [floor][y][x]; single-floor
+    // maps saved before floors existed load as the ground floor
+    #[serde(deserialize_with = "deserialize_floors")]
+    cells: Vec<Vec<Vec<CellType>>>,
     pub size: f32,
     pub width: usize,
     pub height: usize,
+    #[serde(default)]
+    pub topology: Topology,
+
+    #[serde(default, with = "connector_list")]
+    connectors: HashMap<Position, Connector>,
+    // Cost of climbing one floor by stairs, and of one elevator ride
+    #[serde(default = "default_stairs_cost")]
+    pub stairs_cost: i32,
+    #[serde(default = "default_elevator_cost")]
+    pub elevator_cost: i32,
+
+    // Robot footprint radius in cells and the free cells it rules out
+    // (the obstacles inflated into configuration space)
+    #[serde(skip)]
+    robot_radius: f32,
+    #[serde(skip)]
+    inflated: Vec<Vec<Vec<bool>>>,
+
+    // Changes whenever the walls or the grid's shape do, so fields derived
+    // from them can be cached. Unique across grids, so a loaded map never
+    // matches a cache built for another.
+    #[serde(skip, default = "next_revision")]
+    revision: u64,
+}
+
+fn next_revision() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+fn default_stairs_cost() -> i32 {
+    3
+}
+
+fn default_elevator_cost() -> i32 {
+    5
+}
+
+fn deserialize_floors<'de, D>(deserializer: D) -> Result<Vec<Vec<Vec<CellType>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Floors(Vec<Vec<Vec<CellType>>>),
+        Single(Vec<Vec<CellType>>),
+    }
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Floors(floors) if !floors.is_empty() => floors,
+        Stored::Floors(_) => vec![Vec::new()],
+        Stored::Single(cells) => vec![cells],
+    })
+}
+
+/// JSON object keys must be strings, so connectors are stored as a list.
+mod connector_list {
+    use super::{Connector, Position};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S>(
+        map: &HashMap<Position, Connector>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut list: Vec<_> = map.iter().collect();
+        list.sort_by_key(|(pos, _)| (pos.floor, pos.y, pos.x));
+        list.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<Position, Connector>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let list = Vec::<(Position, Connector)>::deserialize(deserializer)?;
+        Ok(list.into_iter().collect())
+    }
 }
 
 impl Grid {
     pub fn new(width: usize, height: usize, size: f32) -> Self {
         Self {
-            cells: vec![vec![CellType::Empty; width]; height],
+            cells: vec![vec![vec![CellType::Empty; width]; height]],
             size,
             width,
             height,
+            topology: Topology::Square,
+            connectors: HashMap::new(),
+            stairs_cost: default_stairs_cost(),
+            elevator_cost: default_elevator_cost(),
+            robot_radius: 0.0,
+            inflated: Vec::new(),
+            revision: next_revision(),
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn floor_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Adds an empty floor on top. Elevator shafts that reach the current
+    /// top floor are extended to it.
+    pub fn add_floor(&mut self) {
+        let top = self.floor_count() as i32 - 1;
+        self.cells
+            .push(vec![vec![CellType::Empty; self.width]; self.height]);
+        self.revision = next_revision();
+        let shafts: Vec<Position> = self
+            .connectors
+            .iter()
+            .filter(|(pos, connector)| pos.floor == top && **connector == Connector::Elevator)
+            .map(|(pos, _)| Position::on_floor(pos.x, pos.y, top + 1))
+            .collect();
+        for pos in shafts {
+            self.connectors.insert(pos, Connector::Elevator);
+        }
+        self.update_inflation();
+    }
+
+    /// Removes the top floor and its connectors; the ground floor stays.
+    pub fn remove_top_floor(&mut self) {
+        if self.floor_count() <= 1 {
+            return;
+        }
+        self.cells.pop();
+        self.revision = next_revision();
+        let floors = self.floor_count() as i32;
+        self.connectors.retain(|pos, _| pos.floor < floors);
+        // Drop connectors that only led to the removed floor
+        let dead_ends: Vec<Position> = self
+            .connectors
+            .keys()
+            .filter(|pos| self.transitions(pos).is_empty())
+            .copied()
+            .collect();
+        for pos in dead_ends {
+            self.connectors.remove(&pos);
+        }
+        self.update_inflation();
+    }
+
+    pub fn connector(&self, pos: &Position) -> Option<Connector> {
+        self.connectors.get(pos).copied()
+    }
+
+    pub fn connectors(&self) -> impl Iterator<Item = (&Position, &Connector)> {
+        self.connectors.iter()
+    }
+
+    /// Places or removes a connector. Obstacle cells can't hold one, so
+    /// placing there does nothing.
+    pub fn set_connector(&mut self, pos: Position, connector: Option<Connector>) {
+        if !self.is_valid_position(&pos) {
+            return;
+        }
+        match connector {
+            Some(connector) => {
+                if self.get_cell(&pos) != CellType::Obstacle {
+                    self.connectors.insert(pos, connector);
+                }
+            }
+            None => {
+                self.connectors.remove(&pos);
+            }
+        }
+    }
+
+    /// Removes the connector at `pos` together with any stairs on the floor
+    /// above or below that it leaves without a partner.
+    pub fn remove_connector(&mut self, pos: Position) {
+        if self.connectors.remove(&pos) != Some(Connector::Stairs) {
+            return;
+        }
+        for floor in [pos.floor - 1, pos.floor + 1] {
+            let other = Position::on_floor(pos.x, pos.y, floor);
+            if self.connector(&other) == Some(Connector::Stairs)
+                && self.transitions(&other).is_empty()
+            {
+                self.connectors.remove(&other);
+            }
+        }
+    }
+
+    /// Cells on other floors reachable through the connector at `pos`,
+    /// whether walkable or not, with the cost of getting there.
+    pub fn transitions(&self, pos: &Position) -> Vec<(Position, i32)> {
+        let Some(connector) = self.connector(pos) else {
+            return Vec::new();
+        };
+        let floors: Vec<i32> = match connector {
+            Connector::Stairs => vec![pos.floor - 1, pos.floor + 1],
+            Connector::Elevator => (0..self.floor_count() as i32)
+                .filter(|floor| *floor != pos.floor)
+                .collect(),
+        };
+        floors
+            .into_iter()
+            .map(|floor| Position::on_floor(pos.x, pos.y, floor))
+            .filter(|other| self.connector(other) == Some(connector))
+            .map(|other| {
+                let cost = match connector {
+                    Connector::Stairs => self.stairs_cost,
+                    Connector::Elevator => self.elevator_cost,
+                };
+                (other, cost)
+            })
+            .collect()
+    }
+
+    /// Cost of moving between neighbouring cells: one step on the same
+    /// floor, the connector's cost between floors.
+    pub fn move_cost(&self, from: &Position, to: &Position) -> i32 {
+        if from.floor == to.floor {
+            return 1;
+        }
+        self.transitions(from)
+            .into_iter()
+            .find(|(other, _)| other == to)
+            .map_or(1, |(_, cost)| cost)
+    }
+
+    pub fn robot_radius(&self) -> f32 {
+        self.robot_radius
+    }
+
+    /// Sets the footprint radius and recomputes the inflated zone.
+    pub fn set_robot_radius(&mut self, radius: f32) {
+        self.robot_radius = radius.max(0.0);
+        self.update_inflation();
+    }
+
+    /// Minkowski sum of the obstacles with the footprint disc: every free
+    /// cell whose centre lies within `robot_radius` of an obstacle centre
+    /// on the same floor (within that many steps on hex grids).
+    fn update_inflation(&mut self) {
+        if self.robot_radius <= 0.0 {
+            self.inflated.clear();
+            return;
+        }
+        let limit = self.robot_radius * self.robot_radius;
+        self.inflated = (0..self.floor_count() as i32)
+            .map(|floor| {
+                distance_field::squared_obstacle_distance(self, floor)
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|d| d > 0.0 && d <= limit).collect())
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Adds the footprint disc around a new obstacle without a full transform.
+    fn inflate_around(&mut self, pos: Position) {
+        let reach = self.robot_radius.floor() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let cell = Position::on_floor(pos.x + dx, pos.y + dy, pos.floor);
+                if self.topology.within(dx, dy, self.robot_radius) && self.is_valid_position(&cell)
+                {
+                    self.inflated[cell.floor as usize][cell.y as usize][cell.x as usize] = true;
+                }
+            }
+        }
+    }
+
+    /// Recomputes the footprint zone around a removed obstacle: only cells
+    /// within `robot_radius` of it can have lost their inflation.
+    fn deflate_around(&mut self, pos: Position) {
+        let reach = self.robot_radius.floor() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let cell = Position::on_floor(pos.x + dx, pos.y + dy, pos.floor);
+                if self.topology.within(dx, dy, self.robot_radius) && self.is_valid_position(&cell)
+                {
+                    let inflated = self.has_obstacle_within_reach(&cell);
+                    self.inflated[cell.floor as usize][cell.y as usize][cell.x as usize] = inflated;
+                }
+            }
         }
     }
 
+    fn has_obstacle_within_reach(&self, pos: &Position) -> bool {
+        let reach = self.robot_radius.floor() as i32;
+        (-reach..=reach).any(|dy| {
+            (-reach..=reach).any(|dx| {
+                let cell = Position::on_floor(pos.x + dx, pos.y + dy, pos.floor);
+                self.topology.within(dx, dy, self.robot_radius)
+                    && self.is_valid_position(&cell)
+                    && self.get_cell(&cell) == CellType::Obstacle
+            })
+        })
+    }
+
+    /// True for free cells the robot cannot occupy because of its footprint.
+    pub fn is_inflated(&self, pos: &Position) -> bool {
+        self.is_valid_position(pos)
+            && self.get_cell(pos) != CellType::Obstacle
+            && self
+                .inflated
+                .get(pos.floor as usize)
+                .and_then(|floor| floor.get(pos.y as usize))
+                .is_some_and(|row| row[pos.x as usize])
+    }
+
+    /// Resizes the grid, keeping existing cells in place relative to `anchor`.
+    /// Returns the offset that was applied to every existing position.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: ResizeAnchor) -> Position {
+        let offset = match anchor {
+            ResizeAnchor::TopLeft => Position::new(0, 0),
+            ResizeAnchor::Center => Position::new(
+                (width as i32 - self.width as i32) / 2,
+                (height as i32 - self.height as i32) / 2,
+            ),
+        };
+        self.reframe(width, height, offset);
+        offset
+    }
+
+    /// Crops the grid to the inclusive rectangle spanned by `a` and `b`.
+    /// Returns the offset that was applied to every remaining position.
+    pub fn crop(&mut self, a: Position, b: Position) -> Position {
+        let min = Position::new(a.x.min(b.x).max(0), a.y.min(b.y).max(0));
+        let max = Position::new(
+            a.x.max(b.x).min(self.width as i32 - 1),
+            a.y.max(b.y).min(self.height as i32 - 1),
+        );
+        let width = (max.x - min.x + 1).max(1) as usize;
+        let height = (max.y - min.y + 1).max(1) as usize;
+        let offset = Position::new(-min.x, -min.y);
+        self.reframe(width, height, offset);
+        offset
+    }
+
+    /// Shifts every floor and connector by `offset` into a `width` × `height`
+    /// frame, dropping what falls outside.
+    fn reframe(&mut self, width: usize, height: usize, offset: Position) {
+        let inside = |x: i32, y: i32| x >= 0 && x < width as i32 && y >= 0 && y < height as i32;
+        for floor in &mut self.cells {
+            let mut cells = vec![vec![CellType::Empty; width]; height];
+            for (y, row) in floor.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    let nx = x as i32 + offset.x;
+                    let ny = y as i32 + offset.y;
+                    if inside(nx, ny) {
+                        cells[ny as usize][nx as usize] = *cell;
+                    }
+                }
+            }
+            *floor = cells;
+        }
+        self.connectors = self
+            .connectors
+            .drain()
+            .map(|(pos, connector)| {
+                let moved = Position::on_floor(pos.x + offset.x, pos.y + offset.y, pos.floor);
+                (moved, connector)
+            })
+            .filter(|(pos, _)| inside(pos.x, pos.y))
+            .collect();
+        self.width = width;
+        self.height = height;
+        self.revision = next_revision();
+        self.update_inflation();
+    }
+
     pub fn get_cell(&self, pos: &Position) -> CellType {
         if self.is_valid_position(pos) {
-            self.cells[pos.y as usize][pos.x as usize]
+            self.cells[pos.floor as usize][pos.y as usize][pos.x as usize]
         } else {
             CellType::Obstacle // Invalid positions are treated as obstacles
         }
     }
 
     pub fn set_cell(&mut self, pos: Position, cell_type: CellType) {
-        if self.is_valid_position(&pos) {
-            self.cells[pos.y as usize][pos.x as usize] = cell_type;
+        if !self.is_valid_position(&pos) {
+            return;
+        }
+        let cell = &mut self.cells[pos.floor as usize][pos.y as usize][pos.x as usize];
+        let was_obstacle = *cell == CellType::Obstacle;
+        *cell = cell_type;
+
+        let is_obstacle = cell_type == CellType::Obstacle;
+        if is_obstacle {
+            self.remove_connector(pos);
+        }
+        if was_obstacle != is_obstacle {
+            self.revision = next_revision();
+        }
+        if self.robot_radius > 0.0 && was_obstacle != is_obstacle {
+            if is_obstacle {
+                self.inflate_around(pos);
+            } else {
+                self.deflate_around(pos);
+            }
         }
     }
 
+    /// Turns every obstacle on every floor into an empty cell, recomputing
+    /// the inflation once rather than per cell.
+    pub fn clear_obstacles(&mut self) {
+        for cell in self.cells.iter_mut().flatten().flatten() {
+            if *cell == CellType::Obstacle {
+                *cell = CellType::Empty;
+            }
+        }
+        self.revision = next_revision();
+        self.update_inflation();
+    }
+
     pub fn is_valid_position(&self, pos: &Position) -> bool {
-        pos.x >= 0 && pos.x < self.width as i32 && pos.y >= 0 && pos.y < self.height as i32
+        pos.x >= 0
+            && pos.x < self.width as i32
+            && pos.y >= 0
+            && pos.y < self.height as i32
+            && pos.floor >= 0
+            && pos.floor < self.floor_count() as i32
     }
 
     pub fn is_walkable(&self, pos: &Position) -> bool {
-        self.is_valid_position(pos) && self.get_cell(pos) != CellType::Obstacle
+        self.is_valid_position(pos)
+            && self.get_cell(pos) != CellType::Obstacle
+            && !self.is_inflated(pos)
+    }
+
+    /// Whether the cell containing a continuous grid coordinate on the
+    /// ground floor is walkable.
+    pub fn is_walkable_point(&self, point: Vec2) -> bool {
+        let (x, y) = self.topology.cell_at(point);
+        self.is_walkable(&Position::new(x, y))
+    }
+
+    /// Walkability of every ground-floor cell, row by row. Planners that
+    /// cache work keep a copy to notice when the map changed under them.
+    pub fn walkability(&self) -> Vec<bool> {
+        (0..self.height as i32)
+            .flat_map(|y| (0..self.width as i32).map(move |x| Position::new(x, y)))
+            .map(|pos| self.is_walkable(&pos))
+            .collect()
+    }
+
+    /// The empty cell on `pos`'s floor fewest steps from `pos`, which may
+    /// lie outside the grid. Obstacles, start and goal are skipped.
+    pub fn nearest_empty_cell(&self, pos: Position) -> Option<Position> {
+        let floor = self.cells.get(pos.floor as usize)?;
+        (0..self.height as i32)
+            .flat_map(|y| (0..self.width as i32).map(move |x| (x, y)))
+            .filter(|&(x, y)| floor[y as usize][x as usize] == CellType::Empty)
+            .map(|(x, y)| Position::on_floor(x, y, pos.floor))
+            .min_by_key(|p| (p.distance_to(&pos, self.topology), p.y, p.x))
     }
 
     pub fn clear_pathfinding_cells(&mut self) {
-        for row in &mut self.cells {
+        for row in self.cells.iter_mut().flatten() {
             for cell in row {
                 match *cell {
                     CellType::Visited | CellType::Frontier | CellType::Current | CellType::Path => {
@@ -111,23 +578,6 @@ impl Grid {
         }
     }
 
-    pub fn mark_visited(
-        &mut self,
-        positions: &[Position],
-        start_pos: Option<Position>,
-        goal_pos: Option<Position>,
-    ) {
-        for &pos in positions {
-            if Some(pos) != start_pos
-                && Some(pos) != goal_pos
-                && self.get_cell(&pos) == CellType::Empty
-                || self.get_cell(&pos) == CellType::Frontier
-            {
-                self.set_cell(pos, CellType::Visited);
-            }
-        }
-    }
-
     pub fn mark_previous_node_as_visited(&mut self, position: Position) {
         self.set_cell(position, CellType::Visited);
     }
@@ -152,10 +602,169 @@ impl Grid {
         self.set_cell(pos, CellType::Current);
     }
 
+    /// Steps between two cells on an empty grid of this topology.
+    pub fn distance(&self, a: &Position, b: &Position) -> i32 {
+        a.distance_to(b, self.topology)
+    }
+
+    /// Walkable neighbours on the same floor, then the cells reachable
+    /// through a connector at `pos`.
     pub fn get_walkable_neighbors(&self, pos: &Position) -> Vec<Position> {
-        pos.neighbors()
+        pos.neighbors(self.topology)
             .into_iter()
+            .chain(self.transitions(pos).into_iter().map(|(other, _)| other))
             .filter(|neighbor| self.is_walkable(neighbor))
             .collect()
     }
+
+    /// Walkable cells connected to `start`, including `start` itself; empty
+    /// when `start` is blocked.
+    pub fn reachable_cells(&self, start: Position) -> HashSet<Position> {
+        if !self.is_walkable(&start) {
+            return HashSet::new();
+        }
+        let mut reachable = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            for neighbor in self.get_walkable_neighbors(&pos) {
+                if reachable.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4×3 grid with obstacles at (0, 0) and (3, 2).
+    fn marked() -> Grid {
+        let mut grid = Grid::new(4, 3, 10.0);
+        grid.set_cell(Position::new(0, 0), CellType::Obstacle);
+        grid.set_cell(Position::new(3, 2), CellType::Obstacle);
+        grid
+    }
+
+    fn obstacles(grid: &Grid) -> Vec<(i32, i32)> {
+        (0..grid.height as i32)
+            .flat_map(|y| (0..grid.width as i32).map(move |x| (x, y)))
+            .filter(|&(x, y)| grid.get_cell(&Position::new(x, y)) == CellType::Obstacle)
+            .collect()
+    }
+
+    #[test]
+    fn resize_top_left_keeps_content_in_place() {
+        let mut grid = marked();
+        assert_eq!(
+            grid.resize(6, 5, ResizeAnchor::TopLeft),
+            Position::new(0, 0)
+        );
+        assert_eq!((grid.width, grid.height), (6, 5));
+        assert_eq!(obstacles(&grid), [(0, 0), (3, 2)]);
+
+        assert_eq!(
+            grid.resize(3, 3, ResizeAnchor::TopLeft),
+            Position::new(0, 0)
+        );
+        assert_eq!(obstacles(&grid), [(0, 0)]);
+    }
+
+    #[test]
+    fn resize_centre_shifts_content_by_half_the_change() {
+        let mut grid = marked();
+        assert_eq!(grid.resize(8, 7, ResizeAnchor::Center), Position::new(2, 2));
+        assert_eq!(obstacles(&grid), [(2, 2), (5, 4)]);
+
+        assert_eq!(
+            grid.resize(4, 3, ResizeAnchor::Center),
+            Position::new(-2, -2)
+        );
+        assert_eq!(obstacles(&grid), [(0, 0), (3, 2)]);
+    }
+
+    #[test]
+    fn crop_keeps_the_selection_and_returns_its_offset() {
+        let mut grid = marked();
+        // Corners in either order, clipped to the grid
+        assert_eq!(
+            grid.crop(Position::new(9, 9), Position::new(1, 1)),
+            Position::new(-1, -1)
+        );
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(obstacles(&grid), [(2, 1)]);
+    }
+
+    #[test]
+    fn nearest_empty_cell_skips_obstacles_and_endpoints() {
+        let mut grid = marked();
+        grid.set_cell(Position::new(3, 1), CellType::Goal);
+        // (3, 2) is an obstacle and (3, 1) the goal
+        assert_eq!(
+            grid.nearest_empty_cell(Position::new(5, 2)),
+            Some(Position::new(2, 2))
+        );
+        assert_eq!(
+            grid.nearest_empty_cell(Position::new(-1, 1)),
+            Some(Position::new(0, 1))
+        );
+        assert_eq!(grid.nearest_empty_cell(Position::on_floor(0, 0, 1)), None);
+    }
+
+    fn two_floors() -> Grid {
+        let mut grid = Grid::new(4, 3, 10.0);
+        grid.add_floor();
+        grid
+    }
+
+    #[test]
+    fn connectors_are_refused_on_obstacles() {
+        let mut grid = two_floors();
+        let wall = Position::on_floor(1, 1, 1);
+        grid.set_cell(wall, CellType::Obstacle);
+        grid.set_connector(wall, Some(Connector::Elevator));
+        assert_eq!(grid.connector(&wall), None);
+        assert_eq!(grid.get_cell(&wall), CellType::Obstacle);
+    }
+
+    #[test]
+    fn removing_stairs_removes_their_partner() {
+        let mut grid = two_floors();
+        let (lower, upper) = (Position::new(2, 1), Position::on_floor(2, 1, 1));
+        grid.set_connector(lower, Some(Connector::Stairs));
+        grid.set_connector(upper, Some(Connector::Stairs));
+        grid.remove_connector(upper);
+        assert_eq!(grid.connectors().count(), 0);
+
+        // Walling off one end does the same
+        grid.set_connector(lower, Some(Connector::Stairs));
+        grid.set_connector(upper, Some(Connector::Stairs));
+        grid.set_cell(lower, CellType::Obstacle);
+        assert_eq!(grid.connectors().count(), 0);
+    }
+
+    #[test]
+    fn stairs_in_a_chain_keep_their_other_partner() {
+        let mut grid = two_floors();
+        grid.add_floor();
+        for floor in 0..3 {
+            grid.set_connector(Position::on_floor(0, 0, floor), Some(Connector::Stairs));
+        }
+        grid.remove_connector(Position::new(0, 0));
+        assert_eq!(grid.connectors().count(), 2);
+        assert_eq!(grid.transitions(&Position::on_floor(0, 0, 1)).len(), 1);
+    }
+
+    #[test]
+    fn removing_the_top_floor_drops_dead_end_connectors() {
+        let mut grid = two_floors();
+        grid.set_connector(Position::new(0, 0), Some(Connector::Stairs));
+        grid.set_connector(Position::on_floor(0, 0, 1), Some(Connector::Stairs));
+        grid.set_connector(Position::new(3, 2), Some(Connector::Elevator));
+        grid.set_connector(Position::on_floor(3, 2, 1), Some(Connector::Elevator));
+        grid.remove_top_floor();
+        assert_eq!(grid.connectors().count(), 0);
+    }
 }
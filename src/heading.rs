@@ -0,0 +1,114 @@
+use crate::position::Position;
+
+/// Cost of a straight move in heading-aware search, in tenths of a cell.
+pub const STRAIGHT_COST: i32 = 10;
+/// Cost of a diagonal move, √2 rounded to tenths.
+pub const DIAGONAL_COST: i32 = 14;
+
+/// One of the eight directions a robot can face, 45° apart, numbered
+/// clockwise from east (screen y points down).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Heading(u8);
+
+const OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+const NAMES: [&str; 8] = ["E", "SE", "S", "SW", "W", "NW", "N", "NE"];
+
+impl Heading {
+    pub const ALL: [Heading; 8] = [
+        Heading(0),
+        Heading(1),
+        Heading(2),
+        Heading(3),
+        Heading(4),
+        Heading(5),
+        Heading(6),
+        Heading(7),
+    ];
+
+    /// Heading of a single move between neighbouring cells.
+    pub fn between(from: &Position, to: &Position) -> Option<Heading> {
+        let offset = (to.x - from.x, to.y - from.y);
+        OFFSETS
+            .iter()
+            .position(|&o| o == offset)
+            .map(|i| Heading(i as u8))
+    }
+
+    pub fn offset(self) -> (i32, i32) {
+        OFFSETS[self.0 as usize]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.0 % 2 == 1
+    }
+
+    /// Angle in radians, clockwise from east.
+    pub fn angle(self) -> f32 {
+        self.0 as f32 * std::f32::consts::FRAC_PI_4
+    }
+
+    /// Rotated clockwise by `steps` × 45° (negative turns left).
+    pub fn turned(self, steps: i32) -> Heading {
+        Heading((self.0 as i32 + steps).rem_euclid(8) as u8)
+    }
+
+    pub fn name(self) -> &'static str {
+        NAMES[self.0 as usize]
+    }
+
+    /// Number of 45° steps in the shortest rotation to `other` (0..=4).
+    pub fn steps_to(self, other: Heading) -> i32 {
+        let diff = (other.0 as i32 - self.0 as i32).rem_euclid(8);
+        diff.min(8 - diff)
+    }
+}
+
+/// Headings of consecutive moves along a path; waits are skipped.
+pub fn path_headings(path: &[Position]) -> Vec<Heading> {
+    path.windows(2)
+        .filter_map(|pair| Heading::between(&pair[0], &pair[1]))
+        .collect()
+}
+
+/// Extra cost of rotating in place, in cells.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TurnPenalties {
+    pub per_45: f32,
+    pub per_90: f32,
+}
+
+impl Default for TurnPenalties {
+    fn default() -> Self {
+        Self {
+            per_45: 0.5,
+            per_90: 1.0,
+        }
+    }
+}
+
+impl TurnPenalties {
+    /// Cost of turning from `from` to `to` in tenths of a cell. Rotations
+    /// are made of 90° turns plus at most one 45° turn.
+    pub fn cost(&self, from: Heading, to: Heading) -> i32 {
+        let steps = from.steps_to(to);
+        let cells = (steps / 2) as f32 * self.per_90 + (steps % 2) as f32 * self.per_45;
+        (cells * STRAIGHT_COST as f32).round() as i32
+    }
+}
+
+/// Cheapest 8-connected travel cost between two cells, ignoring obstacles.
+pub fn octile_distance(a: &Position, b: &Position) -> i32 {
+    let dx = (a.x - b.x).abs();
+    let dy = (a.y - b.y).abs();
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
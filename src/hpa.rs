@@ -0,0 +1,437 @@
+use crate::{grid::Grid, position::Position};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+/// Entrances at least this wide get a transition at each end instead of
+/// a single one in the middle.
+const WIDE_ENTRANCE: i32 = 6;
+
+/// Column and row of a cluster.
+pub type ClusterId = (i32, i32);
+
+/// Abstract graph for hierarchical path-finding (HPA*): the grid is cut
+/// into square clusters, each entrance between two neighbouring clusters
+/// gets transition cells, and the shortest distances between transitions
+/// inside each cluster are cached.
+pub struct Hierarchy {
+    pub cluster_size: i32,
+    width: i32,
+    height: i32,
+    // Transition pairs across each border, keyed by (left/top, right/bottom)
+    borders: HashMap<(ClusterId, ClusterId), Vec<(Position, Position)>>,
+    // Per cluster: distances between its transition cells
+    intra: HashMap<ClusterId, HashMap<Position, Vec<(Position, i32)>>>,
+}
+
+pub struct HpaResult {
+    pub abstract_path: Vec<Position>,
+    pub path: Vec<Position>,
+    pub abstract_expanded: usize,
+    pub refine_expanded: usize,
+}
+
+impl Hierarchy {
+    pub fn build(grid: &Grid, cluster_size: i32) -> Self {
+        let mut hierarchy = Self {
+            cluster_size: cluster_size.max(2),
+            width: grid.width as i32,
+            height: grid.height as i32,
+            borders: HashMap::new(),
+            intra: HashMap::new(),
+        };
+        let clusters: Vec<ClusterId> = hierarchy.clusters().collect();
+        for &cluster in &clusters {
+            for next in [(cluster.0 + 1, cluster.1), (cluster.0, cluster.1 + 1)] {
+                hierarchy.update_border(grid, cluster, next);
+            }
+        }
+        for &cluster in &clusters {
+            hierarchy.update_intra(grid, cluster);
+        }
+        hierarchy
+    }
+
+    /// False once the grid was resized under the hierarchy.
+    pub fn fits(&self, grid: &Grid) -> bool {
+        (self.width, self.height) == (grid.width as i32, grid.height as i32)
+    }
+
+    pub fn clusters(&self) -> impl Iterator<Item = ClusterId> + use<> {
+        let columns = (self.width + self.cluster_size - 1) / self.cluster_size;
+        let rows = (self.height + self.cluster_size - 1) / self.cluster_size;
+        (0..rows).flat_map(move |row| (0..columns).map(move |column| (column, row)))
+    }
+
+    pub fn cluster_of(&self, pos: &Position) -> ClusterId {
+        (pos.x / self.cluster_size, pos.y / self.cluster_size)
+    }
+
+    /// Inclusive cell bounds of a cluster, clipped to the grid.
+    pub fn bounds(&self, cluster: ClusterId) -> (Position, Position) {
+        let min = Position::new(cluster.0 * self.cluster_size, cluster.1 * self.cluster_size);
+        let max = Position::new(
+            (min.x + self.cluster_size).min(self.width) - 1,
+            (min.y + self.cluster_size).min(self.height) - 1,
+        );
+        (min, max)
+    }
+
+    fn contains(&self, cluster: ClusterId, pos: &Position) -> bool {
+        let (min, max) = self.bounds(cluster);
+        (min.x..=max.x).contains(&pos.x) && (min.y..=max.y).contains(&pos.y)
+    }
+
+    fn exists(&self, cluster: ClusterId) -> bool {
+        cluster.0 >= 0
+            && cluster.1 >= 0
+            && cluster.0 * self.cluster_size < self.width
+            && cluster.1 * self.cluster_size < self.height
+    }
+
+    pub fn transitions(&self) -> impl Iterator<Item = &(Position, Position)> {
+        self.borders.values().flatten()
+    }
+
+    /// Cached intra-cluster edges, each listed once.
+    pub fn intra_edges(&self) -> impl Iterator<Item = (Position, Position, i32)> {
+        self.intra.values().flat_map(|nodes| {
+            nodes.iter().flat_map(|(from, edges)| {
+                edges
+                    .iter()
+                    .filter(move |(to, _)| (to.y, to.x) > (from.y, from.x))
+                    .map(move |&(to, cost)| (*from, to, cost))
+            })
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.intra.values().map(HashMap::len).sum()
+    }
+
+    /// Recomputes the transitions across the border between two clusters,
+    /// where `b` is right of or below `a`.
+    fn update_border(&mut self, grid: &Grid, a: ClusterId, b: ClusterId) {
+        if !self.exists(a) || !self.exists(b) {
+            return;
+        }
+        let (min, max) = self.bounds(a);
+        let horizontal = b.0 > a.0;
+        // Pairs of facing cells along the border
+        let pairs: Vec<(Position, Position)> = if horizontal {
+            (min.y..=max.y)
+                .map(|y| (Position::new(max.x, y), Position::new(max.x + 1, y)))
+                .collect()
+        } else {
+            (min.x..=max.x)
+                .map(|x| (Position::new(x, max.y), Position::new(x, max.y + 1)))
+                .collect()
+        };
+
+        let mut transitions = Vec::new();
+        let mut run: Vec<(Position, Position)> = Vec::new();
+        for pair in pairs.into_iter().map(Some).chain([None]) {
+            if let Some((p, q)) = pair
+                && grid.is_walkable(&p)
+                && grid.is_walkable(&q)
+            {
+                run.push((p, q));
+                continue;
+            }
+            if run.len() as i32 >= WIDE_ENTRANCE {
+                transitions.push(run[0]);
+                transitions.push(run[run.len() - 1]);
+            } else if !run.is_empty() {
+                transitions.push(run[run.len() / 2]);
+            }
+            run.clear();
+        }
+        self.borders.insert((a, b), transitions);
+    }
+
+    /// Transition cells that lie inside `cluster`.
+    fn nodes_in(&self, cluster: ClusterId) -> Vec<Position> {
+        let (cx, cy) = cluster;
+        let borders = [
+            ((cx - 1, cy), cluster),
+            (cluster, (cx + 1, cy)),
+            ((cx, cy - 1), cluster),
+            (cluster, (cx, cy + 1)),
+        ];
+        let mut nodes: Vec<Position> = Vec::new();
+        for (p, q) in borders
+            .iter()
+            .filter_map(|key| self.borders.get(key))
+            .flatten()
+        {
+            for cell in [p, q] {
+                if self.contains(cluster, cell) && !nodes.contains(cell) {
+                    nodes.push(*cell);
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Breadth-first search from `from` that never leaves `cluster`.
+    fn cluster_bfs(
+        &self,
+        grid: &Grid,
+        cluster: ClusterId,
+        from: Position,
+    ) -> HashMap<Position, Position> {
+        let mut came_from = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(pos) = queue.pop_front() {
+            for next in grid.get_walkable_neighbors(&pos) {
+                if self.contains(cluster, &next) && !came_from.contains_key(&next) {
+                    came_from.insert(next, pos);
+                    queue.push_back(next);
+                }
+            }
+        }
+        came_from
+    }
+
+    fn update_intra(&mut self, grid: &Grid, cluster: ClusterId) {
+        let nodes = self.nodes_in(cluster);
+        let mut edges = HashMap::new();
+        for &node in &nodes {
+            let came_from = self.cluster_bfs(grid, cluster, node);
+            let reachable = nodes
+                .iter()
+                .filter(|other| **other != node)
+                .filter(|other| came_from.contains_key(other))
+                .map(|other| (*other, path_to(&came_from, *other).len() as i32 - 1))
+                .collect();
+            edges.insert(node, reachable);
+        }
+        self.intra.insert(cluster, edges);
+    }
+
+    /// Refreshes only what a change of walkability between `min` and `max`
+    /// (inclusive) can affect: the borders of the touched clusters and the
+    /// cached distances of those clusters and their neighbours.
+    pub fn update_region(&mut self, grid: &Grid, min: Position, max: Position) {
+        let first = self.cluster_of(&Position::new(min.x.max(0), min.y.max(0)));
+        let last = self.cluster_of(&Position::new(
+            max.x.min(self.width - 1),
+            max.y.min(self.height - 1),
+        ));
+        let mut touched = HashSet::new();
+        for cy in first.1..=last.1 {
+            for cx in first.0..=last.0 {
+                let cluster = (cx, cy);
+                self.update_border(grid, (cx - 1, cy), cluster);
+                self.update_border(grid, cluster, (cx + 1, cy));
+                self.update_border(grid, (cx, cy - 1), cluster);
+                self.update_border(grid, cluster, (cx, cy + 1));
+                for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    touched.insert((cx + dx, cy + dy));
+                }
+            }
+        }
+        for cluster in touched {
+            if self.exists(cluster) {
+                self.update_intra(grid, cluster);
+            }
+        }
+    }
+
+    /// Connects start and goal to their clusters' transitions, searches the
+    /// abstract graph with A*, then refines each abstract edge into cells.
+    pub fn find_path(&self, grid: &Grid, start: Position, goal: Position) -> Option<HpaResult> {
+        if !grid.is_walkable(&start) || !grid.is_walkable(&goal) {
+            return None;
+        }
+        let (start_cluster, goal_cluster) = (self.cluster_of(&start), self.cluster_of(&goal));
+        let distances_from = |cluster: ClusterId, from: Position| -> HashMap<Position, i32> {
+            let came_from = self.cluster_bfs(grid, cluster, from);
+            let mut targets = self.nodes_in(cluster);
+            targets.push(start);
+            targets.push(goal);
+            targets
+                .into_iter()
+                .filter(|t| *t != from && came_from.contains_key(t))
+                .map(|t| (t, path_to(&came_from, t).len() as i32 - 1))
+                .collect()
+        };
+        let start_edges = distances_from(start_cluster, start);
+        let goal_edges = distances_from(goal_cluster, goal);
+
+        let mut links: HashMap<Position, Vec<Position>> = HashMap::new();
+        for &(p, q) in self.transitions() {
+            links.entry(p).or_default().push(q);
+            links.entry(q).or_default().push(p);
+        }
+        let neighbors = |node: Position| -> Vec<(Position, i32)> {
+            let mut result: Vec<(Position, i32)> = if node == start {
+                start_edges.iter().map(|(p, d)| (*p, *d)).collect()
+            } else {
+                self.intra
+                    .get(&self.cluster_of(&node))
+                    .and_then(|nodes| nodes.get(&node))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            if let Some(&d) = goal_edges.get(&node) {
+                result.push((goal, d));
+            }
+            result.extend(links.get(&node).into_iter().flatten().map(|p| (*p, 1)));
+            result
+        };
+
+        let mut g_costs = HashMap::from([(start, 0)]);
+        let mut came_from: HashMap<Position, Position> = HashMap::new();
+        let mut closed = HashSet::new();
+        // Ties on f go to the node closest to the goal
+        let h = start.manhattan_distance_to(&goal);
+        let mut open = BinaryHeap::from([Reverse((h, h, start.x, start.y))]);
+        let mut found = false;
+        while let Some(Reverse((_, _, x, y))) = open.pop() {
+            let node = Position::new(x, y);
+            if !closed.insert(node) {
+                continue;
+            }
+            if node == goal {
+                found = true;
+                break;
+            }
+            for (next, cost) in neighbors(node) {
+                let g = g_costs[&node] + cost;
+                if !closed.contains(&next) && g_costs.get(&next).is_none_or(|&best| g < best) {
+                    g_costs.insert(next, g);
+                    came_from.insert(next, node);
+                    let h = next.manhattan_distance_to(&goal);
+                    open.push(Reverse((g + h, h, next.x, next.y)));
+                }
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        let mut abstract_path = vec![goal];
+        while let Some(&previous) = came_from.get(abstract_path.last().unwrap()) {
+            abstract_path.push(previous);
+        }
+        abstract_path.reverse();
+
+        // Refinement: each abstract edge is either a single step across a
+        // border or a search inside one cluster
+        let mut path = vec![start];
+        let mut refine_expanded = 0;
+        for pair in abstract_path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if from.manhattan_distance_to(&to) == 1
+                && self.cluster_of(&from) != self.cluster_of(&to)
+            {
+                path.push(to);
+                continue;
+            }
+            let came_from = self.cluster_bfs(grid, self.cluster_of(&from), from);
+            refine_expanded += came_from.len();
+            path.extend(path_to(&came_from, to).into_iter().skip(1));
+        }
+
+        Some(HpaResult {
+            abstract_path,
+            path,
+            abstract_expanded: closed.len(),
+            refine_expanded,
+        })
+    }
+}
+
+/// Walks a BFS parent map back from `to`; the root maps to itself.
+fn path_to(came_from: &HashMap<Position, Position>, to: Position) -> Vec<Position> {
+    let mut path = vec![to];
+    let mut current = to;
+    while let Some(&previous) = came_from.get(&current) {
+        if previous == current {
+            break;
+        }
+        path.push(previous);
+        current = previous;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithms::Algorithm, grid::CellType, pathfinding_state::PathfindingState};
+
+    /// 15×15 grid of 5×5 rooms. Each room's right and bottom edges are
+    /// walls with a one-cell door, so every entrance is a single door.
+    fn rooms() -> Grid {
+        let mut grid = Grid::new(15, 15, 10.0);
+        for i in 0..15 {
+            for wall in [4, 9] {
+                if i % 5 != 2 {
+                    grid.set_cell(Position::new(wall, i), CellType::Obstacle);
+                    grid.set_cell(Position::new(i, wall), CellType::Obstacle);
+                }
+            }
+        }
+        grid
+    }
+
+    fn assert_matches_astar(grid: &Grid, hierarchy: &Hierarchy, start: Position, goal: Position) {
+        let result = hierarchy
+            .find_path(grid, start, goal)
+            .expect("an HPA* path");
+        let flat = PathfindingState::solve(&Algorithm::AStar, start, goal, &[], &mut grid.clone())
+            .expect("an A* path");
+        assert_eq!(result.path.first(), Some(&start));
+        assert_eq!(result.path.last(), Some(&goal));
+        for step in result.path.windows(2) {
+            assert_eq!(step[0].manhattan_distance_to(&step[1]), 1);
+            assert!(grid.is_walkable(&step[1]));
+        }
+        assert_eq!(result.path.len(), flat.len(), "{start:?} -> {goal:?}");
+    }
+
+    #[test]
+    fn cost_matches_astar_through_doors() {
+        let grid = rooms();
+        let hierarchy = Hierarchy::build(&grid, 5);
+        let cells = [(0, 0), (3, 3), (12, 1), (7, 7), (1, 13), (13, 13), (6, 0)];
+        for &(sx, sy) in &cells {
+            for &(gx, gy) in &cells {
+                if (sx, sy) != (gx, gy) {
+                    assert_matches_astar(
+                        &grid,
+                        &hierarchy,
+                        Position::new(sx, sy),
+                        Position::new(gx, gy),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cost_matches_astar_within_one_cluster() {
+        let mut grid = Grid::new(10, 10, 10.0);
+        for y in 0..4 {
+            grid.set_cell(Position::new(2, y), CellType::Obstacle);
+        }
+        let hierarchy = Hierarchy::build(&grid, 5);
+        assert_matches_astar(&grid, &hierarchy, Position::new(0, 0), Position::new(4, 0));
+    }
+
+    #[test]
+    fn walled_off_goal_has_no_path() {
+        let mut grid = Grid::new(10, 10, 10.0);
+        for i in 0..10 {
+            grid.set_cell(Position::new(i, 5), CellType::Obstacle);
+        }
+        let hierarchy = Hierarchy::build(&grid, 5);
+        assert!(
+            hierarchy
+                .find_path(&grid, Position::new(0, 0), Position::new(9, 9))
+                .is_none()
+        );
+    }
+}
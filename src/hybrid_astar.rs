@@ -0,0 +1,307 @@
+use crate::{grid::Grid, position::Position, reeds_shepp};
+use egui::Vec2;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::f32::consts::TAU;
+
+/// Distance between the axles, in cells.
+pub const WHEELBASE: f32 = 1.0;
+/// Arc length driven by one expansion; long enough to leave the current cell.
+const STEP: f32 = 1.5;
+/// Spacing of collision checks along a motion.
+const CHECK_STEP: f32 = 0.2;
+const HEADING_BINS: i32 = 16;
+const EXPANSION_LIMIT: usize = 30_000;
+
+const REVERSE_COST: f32 = 2.0;
+const SWITCH_COST: f32 = 3.0;
+const STEER_COST: f32 = 0.2;
+
+/// A continuous vehicle pose in grid units (cell centres at +0.5), with
+/// `theta` in radians clockwise from east.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    pub theta: f32,
+}
+
+impl Pose {
+    /// Pose at the centre of `cell`.
+    pub fn at_cell(cell: Position, theta: f32) -> Self {
+        Self {
+            x: cell.x as f32 + 0.5,
+            y: cell.y as f32 + 0.5,
+            theta,
+        }
+    }
+
+    pub fn point(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    fn cell(&self) -> Position {
+        Position::new(self.x.floor() as i32, self.y.floor() as i32)
+    }
+
+    /// Discrete search bin: the grid cell plus a heading sector.
+    fn bin(&self) -> (Position, i32) {
+        let sector = TAU / HEADING_BINS as f32;
+        let heading = (self.theta.rem_euclid(TAU) / sector).round() as i32 % HEADING_BINS;
+        (self.cell(), heading)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HybridConfig {
+    pub max_steering_deg: f32,
+    pub allow_reverse: bool,
+    // Try a Reeds-Shepp shot to the goal every this many expansions
+    pub analytic_interval: usize,
+}
+
+impl Default for HybridConfig {
+    fn default() -> Self {
+        Self {
+            max_steering_deg: 35.0,
+            allow_reverse: true,
+            analytic_interval: 5,
+        }
+    }
+}
+
+impl HybridConfig {
+    /// Minimum turning radius of the bicycle model at full lock.
+    pub fn turning_radius(&self) -> f32 {
+        WHEELBASE / self.max_steering_deg.to_radians().tan()
+    }
+}
+
+pub struct HybridPlan {
+    // Poses along the trajectory, flagged true while reversing
+    pub trajectory: Vec<(Pose, bool)>,
+    // Index in `trajectory` where the Reeds-Shepp shot begins
+    pub analytic_start: usize,
+    // Parent → child motions of every expanded node, for drawing the tree
+    pub tree: Vec<Vec<Vec2>>,
+    pub expanded: usize,
+    pub length: f32,
+    pub direction_changes: usize,
+}
+
+pub enum HybridError {
+    StartBlocked,
+    GoalBlocked,
+    NoPath(usize),
+    ExpansionLimit,
+}
+
+struct SearchNode {
+    pose: Pose,
+    g: f32,
+    reverse: bool,
+    parent: Option<usize>,
+    // Samples driven from the parent to this node
+    motion: Vec<(Pose, bool)>,
+}
+
+/// Hybrid A*: searches continuous poses with a bicycle model, keeping one
+/// node per (cell, heading sector), and periodically tries to finish with
+/// an analytic Reeds-Shepp path to the exact goal pose.
+pub fn plan(
+    grid: &Grid,
+    start: Pose,
+    goal: Pose,
+    config: &HybridConfig,
+) -> Result<HybridPlan, HybridError> {
+    if !grid.is_walkable(&start.cell()) {
+        return Err(HybridError::StartBlocked);
+    }
+    // No analytic shot could ever end on a blocked goal
+    if !grid.is_walkable(&goal.cell()) {
+        return Err(HybridError::GoalBlocked);
+    }
+    let radius = config.turning_radius();
+    let distances = obstacle_aware_distance(grid, goal.cell());
+    let heuristic = |pose: &Pose| {
+        let euclidean = (pose.point() - goal.point()).length();
+        let grid_distance = distances
+            .get(&pose.cell())
+            .map_or(f32::INFINITY, |d| (d - 1.0).max(0.0));
+        euclidean.max(grid_distance)
+    };
+    // Priorities are stored as fixed-point so the heap can order them
+    let key = |f: f32| (f * 1000.0) as i64;
+
+    let mut nodes = vec![SearchNode {
+        pose: start,
+        g: 0.0,
+        reverse: false,
+        parent: None,
+        motion: Vec::new(),
+    }];
+    let mut open = BinaryHeap::from([Reverse((key(heuristic(&start)), 0usize))]);
+    let mut best_g: HashMap<(Position, i32), f32> = HashMap::from([(start.bin(), 0.0)]);
+    let mut closed = HashSet::new();
+    let mut tree = Vec::new();
+    let mut expanded = 0;
+
+    let curvature = 1.0 / radius;
+    let steerings = [curvature, 0.0, -curvature];
+    let directions: &[f32] = if config.allow_reverse {
+        &[1.0, -1.0]
+    } else {
+        &[1.0]
+    };
+
+    while let Some(Reverse((_, index))) = open.pop() {
+        let pose = nodes[index].pose;
+        if !closed.insert(pose.bin()) {
+            continue;
+        }
+        expanded += 1;
+        if expanded > EXPANSION_LIMIT {
+            return Err(HybridError::ExpansionLimit);
+        }
+        if !nodes[index].motion.is_empty() {
+            tree.push(nodes[index].motion.iter().map(|(p, _)| p.point()).collect());
+        }
+
+        let interval = config.analytic_interval.max(1);
+        let near_goal = (pose.point() - goal.point()).length() < 4.0 * radius;
+        if (expanded % interval == 0 || near_goal)
+            && let Some(shot) = reeds_shepp::shortest_path(pose, goal, radius)
+        {
+            let samples = shot.sample(pose, CHECK_STEP);
+            if samples.iter().all(|(p, _)| grid.is_walkable(&p.cell())) {
+                return Ok(build_plan(&nodes, index, samples, tree, expanded));
+            }
+        }
+
+        for &direction in directions {
+            for &steer in &steerings {
+                let reverse = direction < 0.0;
+                let checks = (STEP / CHECK_STEP).ceil() as usize;
+                let mut motion = Vec::with_capacity(checks);
+                let mut blocked = false;
+                for i in 1..=checks {
+                    let sample = reeds_shepp::advance(
+                        pose,
+                        steer,
+                        direction * STEP * i as f32 / checks as f32,
+                    );
+                    if !grid.is_walkable(&sample.cell()) {
+                        blocked = true;
+                        break;
+                    }
+                    motion.push((sample, reverse));
+                }
+                if blocked {
+                    continue;
+                }
+                let next = motion.last().unwrap().0;
+                let bin = next.bin();
+                if closed.contains(&bin) {
+                    continue;
+                }
+
+                let mut cost = STEP;
+                if reverse {
+                    cost *= REVERSE_COST;
+                }
+                if steer != 0.0 {
+                    cost += STEER_COST;
+                }
+                if index != 0 && reverse != nodes[index].reverse {
+                    cost += SWITCH_COST;
+                }
+                let g = nodes[index].g + cost;
+                if best_g.get(&bin).is_some_and(|&existing| existing <= g) {
+                    continue;
+                }
+                best_g.insert(bin, g);
+                nodes.push(SearchNode {
+                    pose: next,
+                    g,
+                    reverse,
+                    parent: Some(index),
+                    motion,
+                });
+                open.push(Reverse((key(g + heuristic(&next)), nodes.len() - 1)));
+            }
+        }
+    }
+    Err(HybridError::NoPath(expanded))
+}
+
+/// 8-connected shortest distances from `goal` around obstacles; the
+/// holonomic-with-obstacles heuristic of Hybrid A*.
+fn obstacle_aware_distance(grid: &Grid, goal: Position) -> HashMap<Position, f32> {
+    let mut distance = HashMap::from([(goal, 0.0f32)]);
+    let mut open = BinaryHeap::from([Reverse((0i64, goal.x, goal.y))]);
+    while let Some(Reverse((d, x, y))) = open.pop() {
+        let pos = Position::new(x, y);
+        let d = d as f32 / 1000.0;
+        if distance.get(&pos).is_some_and(|&best| best < d) {
+            continue;
+        }
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let next = Position::new(x + dx, y + dy);
+                if (dx, dy) == (0, 0) || !grid.is_walkable(&next) {
+                    continue;
+                }
+                let step = if dx != 0 && dy != 0 { 2f32.sqrt() } else { 1.0 };
+                let nd = d + step;
+                if distance.get(&next).is_none_or(|&best| nd < best) {
+                    distance.insert(next, nd);
+                    open.push(Reverse(((nd * 1000.0) as i64, next.x, next.y)));
+                }
+            }
+        }
+    }
+    distance
+}
+
+fn build_plan(
+    nodes: &[SearchNode],
+    last: usize,
+    shot: Vec<(Pose, bool)>,
+    tree: Vec<Vec<Vec2>>,
+    expanded: usize,
+) -> HybridPlan {
+    let mut chain = vec![last];
+    while let Some(parent) = nodes[*chain.last().unwrap()].parent {
+        chain.push(parent);
+    }
+    chain.reverse();
+
+    let mut trajectory = vec![(nodes[chain[0]].pose, false)];
+    for &index in &chain[1..] {
+        trajectory.extend(nodes[index].motion.iter().copied());
+    }
+    let analytic_start = trajectory.len() - 1;
+    trajectory.extend(shot.into_iter().skip(1));
+    // The start pose drives off in the direction of the first motion
+    if let Some(&(_, reverse)) = trajectory.get(1) {
+        trajectory[0].1 = reverse;
+    }
+
+    let length = trajectory
+        .windows(2)
+        .map(|pair| (pair[1].0.point() - pair[0].0.point()).length())
+        .sum();
+    let direction_changes = trajectory
+        .windows(2)
+        .filter(|pair| pair[0].1 != pair[1].1)
+        .count();
+
+    HybridPlan {
+        trajectory,
+        analytic_start,
+        tree,
+        expanded,
+        length,
+        direction_changes,
+    }
+}
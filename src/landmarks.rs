@@ -0,0 +1,119 @@
+use crate::{grid::Grid, position::Position, topology::Topology};
+use std::collections::VecDeque;
+
+/// Landmarks with a full table of shortest-path distances from each, used
+/// for the ALT (A*, Landmarks, Triangle inequality) heuristic.
+pub struct Landmarks {
+    pub positions: Vec<Position>,
+    // One table per landmark, indexed [y][x]; `None` where unreachable
+    tables: Vec<Vec<Vec<Option<i32>>>>,
+    // Walkability when the tables were computed, to detect stale tables
+    snapshot: Vec<bool>,
+    topology: Topology,
+}
+
+impl Landmarks {
+    pub fn compute(grid: &Grid, positions: Vec<Position>) -> Self {
+        let tables = positions.iter().map(|p| distance_table(grid, *p)).collect();
+        Self {
+            positions,
+            tables,
+            snapshot: grid.walkability(),
+            topology: grid.topology,
+        }
+    }
+
+    /// Farthest-point selection: the first landmark is the cell farthest
+    /// from `seed`, each next one the cell farthest from all landmarks so far.
+    pub fn farthest(grid: &Grid, seed: Position, count: usize) -> Self {
+        let mut positions = Vec::new();
+        let mut tables: Vec<Vec<Vec<Option<i32>>>> = Vec::new();
+        let mut nearest = distance_table(grid, seed);
+        for _ in 0..count {
+            let farthest = nearest
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.iter()
+                        .enumerate()
+                        .filter_map(move |(x, d)| d.map(|d| (d, Position::new(x as i32, y as i32))))
+                })
+                .max_by_key(|(d, pos)| (*d, -pos.y, -pos.x));
+            let Some((d, landmark)) = farthest else {
+                break;
+            };
+            if d == 0 && !positions.is_empty() {
+                break;
+            }
+            let table = distance_table(grid, landmark);
+            for (row, new_row) in nearest.iter_mut().zip(&table) {
+                for (cell, new) in row.iter_mut().zip(new_row) {
+                    if let (Some(current), Some(new)) = (cell.as_mut(), new) {
+                        *current = (*current).min(*new);
+                    }
+                }
+            }
+            positions.push(landmark);
+            tables.push(table);
+        }
+        Self {
+            positions,
+            tables,
+            snapshot: grid.walkability(),
+            topology: grid.topology,
+        }
+    }
+
+    /// True if obstacles or the cell shape changed since the tables were
+    /// computed.
+    pub fn is_stale(&self, grid: &Grid) -> bool {
+        self.topology != grid.topology || self.snapshot != grid.walkability()
+    }
+
+    pub fn distance(&self, landmark: usize, pos: &Position) -> Option<i32> {
+        self.tables[landmark]
+            .get(pos.y as usize)
+            .and_then(|row| row.get(pos.x as usize))
+            .copied()
+            .flatten()
+    }
+
+    pub fn max_distance(&self, landmark: usize) -> i32 {
+        self.tables[landmark]
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Lower bound on the distance from `pos` to `goal`: by the triangle
+    /// inequality |d(L, goal) − d(L, pos)| for every landmark L, never less
+    /// than the distance on an empty grid.
+    pub fn heuristic(&self, pos: &Position, goal: &Position) -> i32 {
+        (0..self.tables.len())
+            .filter_map(|i| Some((self.distance(i, goal)? - self.distance(i, pos)?).abs()))
+            .fold(pos.distance_to(goal, self.topology), i32::max)
+    }
+}
+
+/// Breadth-first distances from `from` to every reachable cell.
+fn distance_table(grid: &Grid, from: Position) -> Vec<Vec<Option<i32>>> {
+    let mut table = vec![vec![None; grid.width]; grid.height];
+    if !grid.is_walkable(&from) {
+        return table;
+    }
+    table[from.y as usize][from.x as usize] = Some(0);
+    let mut queue = VecDeque::from([(from, 0)]);
+    while let Some((pos, d)) = queue.pop_front() {
+        for next in grid.get_walkable_neighbors(&pos) {
+            let slot = &mut table[next.y as usize][next.x as usize];
+            if slot.is_none() {
+                *slot = Some(d + 1);
+                queue.push_back((next, d + 1));
+            }
+        }
+    }
+    table
+}
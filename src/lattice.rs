@@ -0,0 +1,239 @@
+use crate::{
+    grid::Grid,
+    heading::{Heading, STRAIGHT_COST},
+    position::Position,
+};
+use egui::Vec2;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::f32::consts::FRAC_PI_2;
+
+/// Lattice states expanded before the planner gives up.
+const EXPANSION_LIMIT: usize = 200_000;
+
+/// Headings the lattice uses: the four axis directions.
+pub const HEADINGS: [Heading; 4] = [
+    Heading::ALL[0],
+    Heading::ALL[2],
+    Heading::ALL[4],
+    Heading::ALL[6],
+];
+
+/// A lattice state: cell and heading.
+type State = (Position, Heading);
+
+/// A short drivable motion from one lattice state to another.
+#[derive(Clone, Debug)]
+pub struct Primitive {
+    pub end: Position,
+    pub end_heading: Heading,
+    // Arc length in tenths of a cell
+    pub cost: i32,
+    // Points along the motion relative to the start cell centre
+    pub points: Vec<Vec2>,
+    // Cells the motion passes through, relative to the start cell
+    swept: Vec<Position>,
+}
+
+impl Primitive {
+    fn new(points: Vec<Vec2>, end_heading: Heading) -> Self {
+        let last = *points.last().unwrap();
+        let length: f32 = points.windows(2).map(|p| (p[1] - p[0]).length()).sum();
+        let cell = |p: Vec2| Position::new((p.x + 0.5).floor() as i32, (p.y + 0.5).floor() as i32);
+
+        let mut swept = Vec::new();
+        for pair in points.windows(2) {
+            // Dense sampling so no touched cell is missed between points
+            for i in 0..=10 {
+                let pos = cell(pair[0] + (pair[1] - pair[0]) * (i as f32 / 10.0));
+                if !swept.contains(&pos) {
+                    swept.push(pos);
+                }
+            }
+        }
+        Self {
+            end: Position::new(last.x.round() as i32, last.y.round() as i32),
+            end_heading,
+            cost: (length * STRAIGHT_COST as f32).round() as i32,
+            points,
+            swept,
+        }
+    }
+}
+
+/// Motion primitives for every lattice heading: one cell straight ahead and
+/// quarter-circle arcs to the left and right with a fixed turning radius.
+pub struct Lattice {
+    primitives: HashMap<Heading, Vec<Primitive>>,
+}
+
+impl Lattice {
+    pub fn new(turning_radius: i32) -> Self {
+        let radius = turning_radius.max(1) as f32;
+        let samples = 8 * turning_radius.max(1) as usize;
+        let arc = |side: f32| -> Vec<Vec2> {
+            (0..=samples)
+                .map(|i| {
+                    let theta = FRAC_PI_2 * i as f32 / samples as f32;
+                    Vec2::new(radius * theta.sin(), side * radius * (1.0 - theta.cos()))
+                })
+                .collect()
+        };
+
+        let mut primitives = HashMap::new();
+        for heading in HEADINGS {
+            let rotate = |points: Vec<Vec2>| -> Vec<Vec2> {
+                let (sin, cos) = heading.angle().sin_cos();
+                points
+                    .into_iter()
+                    .map(|p| Vec2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos))
+                    .collect()
+            };
+            primitives.insert(
+                heading,
+                vec![
+                    Primitive::new(rotate(vec![Vec2::ZERO, Vec2::new(1.0, 0.0)]), heading),
+                    // Screen y points down, so a left turn bends towards -y
+                    Primitive::new(rotate(arc(-1.0)), heading.turned(-2)),
+                    Primitive::new(rotate(arc(1.0)), heading.turned(2)),
+                ],
+            );
+        }
+        Self { primitives }
+    }
+
+    pub fn primitives(&self, heading: Heading) -> &[Primitive] {
+        &self.primitives[&heading]
+    }
+}
+
+pub struct LatticePlan {
+    // Continuous trajectory in grid coordinates (cell centres at +0.5)
+    pub trajectory: Vec<Vec2>,
+    pub poses: Vec<(Position, Heading)>,
+    pub swept: Vec<Position>,
+    pub length: f32,
+    pub expanded: usize,
+}
+
+pub enum LatticeError {
+    NoPath(usize),
+    ExpansionLimit,
+}
+
+/// A* over (cell, heading) lattice states. `start_heading` of `None` lets
+/// the robot start facing any axis direction; any heading at the goal is
+/// accepted.
+pub fn plan(
+    grid: &Grid,
+    lattice: &Lattice,
+    start: Position,
+    start_heading: Option<Heading>,
+    goal: Position,
+) -> Result<LatticePlan, LatticeError> {
+    // Straight-line distance never overestimates the length of a curve
+    let h = |pos: Position| {
+        let (dx, dy) = ((pos.x - goal.x) as f32, (pos.y - goal.y) as f32);
+        ((dx * dx + dy * dy).sqrt() * STRAIGHT_COST as f32).floor() as i32
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut g_costs: HashMap<State, i32> = HashMap::new();
+    let mut came_from: HashMap<State, (State, usize)> = HashMap::new();
+    let mut closed = HashSet::new();
+    for heading in start_heading.map_or(HEADINGS.to_vec(), |heading| vec![heading]) {
+        g_costs.insert((start, heading), 0);
+        open.push(Reverse((h(start), 0, start.x, start.y, heading)));
+    }
+
+    while let Some(Reverse((_, g, x, y, heading))) = open.pop() {
+        let state = (Position::new(x, y), heading);
+        if !closed.insert(state) {
+            continue;
+        }
+        if closed.len() > EXPANSION_LIMIT {
+            return Err(LatticeError::ExpansionLimit);
+        }
+        if state.0 == goal {
+            return Ok(build_plan(lattice, &came_from, state, closed.len()));
+        }
+
+        for (index, primitive) in lattice.primitives(heading).iter().enumerate() {
+            let end = Position::new(state.0.x + primitive.end.x, state.0.y + primitive.end.y);
+            let next = (end, primitive.end_heading);
+            if closed.contains(&next) || !is_clear(grid, state.0, primitive) {
+                continue;
+            }
+            let tentative_g = g + primitive.cost;
+            if g_costs
+                .get(&next)
+                .is_none_or(|&existing| tentative_g < existing)
+            {
+                g_costs.insert(next, tentative_g);
+                came_from.insert(next, (state, index));
+                open.push(Reverse((
+                    tentative_g + h(end),
+                    tentative_g,
+                    end.x,
+                    end.y,
+                    primitive.end_heading,
+                )));
+            }
+        }
+    }
+    Err(LatticeError::NoPath(closed.len()))
+}
+
+/// Collision check of every cell the primitive sweeps when started at `from`.
+fn is_clear(grid: &Grid, from: Position, primitive: &Primitive) -> bool {
+    primitive
+        .swept
+        .iter()
+        .all(|cell| grid.is_walkable(&Position::new(from.x + cell.x, from.y + cell.y)))
+}
+
+fn build_plan(
+    lattice: &Lattice,
+    came_from: &HashMap<State, (State, usize)>,
+    goal: State,
+    expanded: usize,
+) -> LatticePlan {
+    let mut steps = Vec::new();
+    let mut current = goal;
+    while let Some(&(parent, index)) = came_from.get(&current) {
+        steps.push((parent, index));
+        current = parent;
+    }
+    steps.reverse();
+
+    let mut trajectory = vec![Vec2::new(
+        current.0.x as f32 + 0.5,
+        current.0.y as f32 + 0.5,
+    )];
+    let mut poses = vec![current];
+    let mut swept = vec![current.0];
+    for ((from, heading), index) in steps {
+        let primitive = &lattice.primitives(heading)[index];
+        let origin = Vec2::new(from.x as f32 + 0.5, from.y as f32 + 0.5);
+        trajectory.extend(primitive.points.iter().skip(1).map(|p| origin + *p));
+        for cell in &primitive.swept {
+            let cell = Position::new(from.x + cell.x, from.y + cell.y);
+            if !swept.contains(&cell) {
+                swept.push(cell);
+            }
+        }
+        poses.push((
+            Position::new(from.x + primitive.end.x, from.y + primitive.end.y),
+            primitive.end_heading,
+        ));
+    }
+    let length = trajectory.windows(2).map(|p| (p[1] - p[0]).length()).sum();
+
+    LatticePlan {
+        trajectory,
+        poses,
+        swept,
+        length,
+        expanded,
+    }
+}
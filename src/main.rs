@@ -1,21 +1,63 @@
 use eframe::egui;
 
 mod algorithms;
+mod comparison;
+mod coverage;
+mod distance_field;
+mod dynamic_obstacle;
+mod exploration;
+mod flow_field;
 mod grid;
+mod heading;
+mod hpa;
+mod hybrid_astar;
+mod landmarks;
+mod lattice;
 mod map_handler;
+mod mapf;
 mod node;
 mod pathfinding_state;
 mod position;
+mod potential_field;
+mod reeds_shepp;
+mod robot;
+mod routing;
+mod sampling;
+mod smoothing;
 mod theme;
 mod tools;
+mod topology;
+mod viewport;
+mod visibility;
 
 use algorithms::Algorithm;
+use comparison::{Comparison, RunStatus};
+use coverage::{CoveragePattern, CoverageRun};
+use distance_field::DistanceCache;
+use dynamic_obstacle::DynamicObstacle;
+use exploration::{BeliefMap, FrontierExplorer, SensorSim, SimStatus, UnknownPolicy};
+use flow_field::{FlowAgent, FlowField};
 // use egui::ImageSource;
-use grid::{CellType, Grid};
+use grid::{CellType, Connector, Grid, ResizeAnchor};
+use heading::{Heading, TurnPenalties};
+use hpa::{Hierarchy, HpaResult};
+use hybrid_astar::{HybridConfig, HybridError, HybridPlan, Pose};
+use landmarks::Landmarks;
+use lattice::{Lattice, LatticeError, LatticePlan};
+use mapf::{Agent, MapfError, MapfRun, MapfSolver};
 use pathfinding_state::PathfindingState;
 use position::Position;
+use potential_field::{DescentStatus, PotentialConfig, PotentialRun};
+use robot::Robot;
+use routing::{RoutingError, Tour};
+use sampling::{Roadmap, SamplingConfig, SamplingPlanner, TreeRun};
+use smoothing::{Smoothing, SmoothingOptions};
+use std::rc::Rc;
 use theme::Theme;
 use tools::Tool;
+use topology::Topology;
+use viewport::Viewport;
+use visibility::VisibilityGraph;
 
 // const CELL_SIZE: f32 = 25.0;
 // const GITHUB_MARK: ImageSource = egui::include_image!("../assets/github-mark.svg");
@@ -67,11 +109,22 @@ const DEFAULT_OBSTACLES: [[i32; 2]; 44] = [
     [20, 8],
 ];
 
+const AGENT_COLORS: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(66, 133, 244),
+    egui::Color32::from_rgb(219, 68, 55),
+    egui::Color32::from_rgb(244, 180, 0),
+    egui::Color32::from_rgb(15, 157, 88),
+    egui::Color32::from_rgb(171, 71, 188),
+    egui::Color32::from_rgb(0, 172, 193),
+    egui::Color32::from_rgb(255, 112, 67),
+    egui::Color32::from_rgb(121, 85, 72),
+];
+
 pub struct RoboNav {
     grid: Grid,
     start_pos: Option<Position>,
     goal_pos: Option<Position>,
-    // robot_pos: Option<Position>,
+    robot: Robot,
     current_algorithm: Algorithm,
     is_solving: bool,
     solving_step: usize,
@@ -81,16 +134,147 @@ pub struct RoboNav {
     show_heuristics: bool,
     show_costs: bool,
     show_parent_arrows: bool,
+    show_visit_order: bool,
+    visit_order_style: VisitOrderStyle,
     step_by_step: bool,
     auto_solve_speed: f32,
     last_step_time: f64,
     selected_tool: Tool,
     algorithm_info: String,
 
+    // Offset of the grid origin from the top-left of the grid area
+    pan_offset: egui::Vec2,
+    show_minimap: bool,
+
+    // Pending grid dimensions edited in the side panel
+    resize_width: usize,
+    resize_height: usize,
+    resize_anchor: ResizeAnchor,
+    clamp_endpoints: bool,
+    selection: Option<(Position, Position)>,
+
+    // Side-by-side comparison of several algorithms
+    comparison_mode: bool,
+    comparison_algorithms: Vec<Algorithm>,
+    comparison: Option<Comparison>,
+
+    // Fog-of-war navigation with a limited sensor range
+    exploration: Option<SensorSim>,
+    frontier_explorer: Option<FrontierExplorer>,
+    sensor_radius: i32,
+    unknown_policy: UnknownPolicy,
+
+    // Coverage sweep over every reachable free cell
+    coverage_pattern: CoveragePattern,
+    coverage: Option<CoverageRun>,
+
+    // Multi-goal routing through placed waypoints
+    waypoints: Vec<Position>,
+    waypoints_ordered: bool,
+    tour: Option<Tour>,
+    tour_error: Option<String>,
+
+    // Multi-agent path finding
+    agents: Vec<Agent>,
+    pending_agent_start: Option<Position>,
+    mapf_solver: MapfSolver,
+    mapf: Option<MapfRun>,
+    mapf_error: Option<String>,
+
+    // Obstacles moving on looping routes, avoided by space-time A*
+    dynamic_obstacles: Vec<DynamicObstacle>,
+    pending_obstacle_route: Vec<Position>,
+    obstacle_preview_time: usize,
+
+    // Distance-to-obstacle overlay and A* clearance penalty
+    show_distance_field: bool,
+    clearance_weight: f32,
+    distance_cache: DistanceCache,
+
+    // Continuous polyline post-processed from `final_path`
+    smoothing: SmoothingOptions,
+    smoothed_path: Vec<egui::Vec2>,
+
+    // Rotation costs for heading-aware A*
+    turn_penalties: TurnPenalties,
+    show_heading_arrows: bool,
+
+    // Car-like planning over a lattice of motion primitives
+    lattice_radius: i32,
+    lattice_start_heading: Option<Heading>,
+    lattice_plan: Option<LatticePlan>,
+    lattice_error: Option<String>,
+
+    // Hybrid A* for non-holonomic vehicles (parking manoeuvres)
+    hybrid_config: HybridConfig,
+    hybrid_start_heading: Heading,
+    hybrid_goal_heading: Heading,
+    hybrid_plan: Option<HybridPlan>,
+    hybrid_error: Option<String>,
+    show_hybrid_tree: bool,
+
+    // Sampling-based planners in continuous space; the PRM roadmap
+    // survives clearing so it can be queried again
+    sampling_planner: SamplingPlanner,
+    sampling_config: SamplingConfig,
+    tree_run: Option<TreeRun>,
+    roadmap: Option<Roadmap>,
+    roadmap_path: Option<Vec<egui::Vec2>>,
+    sampling_error: Option<String>,
+
+    // Artificial potential field descent
+    potential_config: PotentialConfig,
+    potential: Option<PotentialRun>,
+    show_potential_field: bool,
+
+    // Goal-wide flow field followed by any number of agents
+    flow_field: Option<FlowField>,
+    flow_agents: Vec<FlowAgent>,
+    flow_playing: bool,
+    flow_speed: f32,
+    show_flow_distance: bool,
+
+    // Hierarchical path-finding; the cluster graph survives clearing and is
+    // patched incrementally when walls are edited
+    hpa_cluster_size: i32,
+    hierarchy: Option<Hierarchy>,
+    hpa_result: Option<HpaResult>,
+    hpa_flat_expanded: Option<usize>,
+    hpa_error: Option<String>,
+    show_hpa_overlay: bool,
+    show_hpa_edges: bool,
+
+    // ALT landmarks: placed or farthest-point selected, with distance tables
+    landmark_positions: Vec<Position>,
+    landmark_count: usize,
+    landmarks: Option<Rc<Landmarks>>,
+    use_alt: bool,
+    // Nodes expanded by A* with (ALT, Manhattan) heuristics
+    alt_comparison: Option<(usize, usize)>,
+    shown_landmark_table: Option<usize>,
+
+    // Visibility graph over obstacle corners: exact Euclidean reference path
+    visibility: Option<VisibilityGraph>,
+    // Length of the grid A* path between the same endpoints, for comparison
+    visibility_astar_length: Option<usize>,
+    visibility_error: Option<String>,
+    show_visibility_edges: bool,
+    show_visibility_polygons: bool,
+
+    // Floor shown in the grid view and edited by the tools
+    current_floor: i32,
+
     ui: UIState,
     theme: Theme,
 }
 
+/// How the expansion order of closed nodes is drawn over the grid.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VisitOrderStyle {
+    Numbers,
+    Heatmap,
+}
+
 // Simple UI state struct to avoid borrowing conflicts
 pub struct UIState {
     pub show_inspector: bool,
@@ -125,6 +309,7 @@ impl Default for RoboNav {
             grid,
             start_pos: Some(Position::new(1, 1)),
             goal_pos: Some(Position::new(17, 10)),
+            robot: Robot::default(),
             current_algorithm: Algorithm::AStar,
             is_solving: false,
             solving_step: 0,
@@ -134,12 +319,112 @@ impl Default for RoboNav {
             show_heuristics: false,
             show_costs: false,
             show_parent_arrows: false,
+            show_visit_order: false,
+            visit_order_style: VisitOrderStyle::Heatmap,
             step_by_step: false,
             auto_solve_speed: 0.0,
             last_step_time: 0.0,
             selected_tool: Tool::SetStart,
             algorithm_info: String::new(),
 
+            pan_offset: egui::Vec2::ZERO,
+            show_minimap: true,
+
+            resize_width: width,
+            resize_height: height,
+            resize_anchor: ResizeAnchor::TopLeft,
+            clamp_endpoints: true,
+            selection: None,
+
+            comparison_mode: false,
+            comparison_algorithms: Algorithm::ALL.to_vec(),
+            comparison: None,
+
+            exploration: None,
+            frontier_explorer: None,
+            sensor_radius: 4,
+            unknown_policy: UnknownPolicy::AssumeFree,
+
+            coverage_pattern: CoveragePattern::Boustrophedon,
+            coverage: None,
+
+            waypoints: Vec::new(),
+            waypoints_ordered: false,
+            tour: None,
+            tour_error: None,
+
+            agents: Vec::new(),
+            pending_agent_start: None,
+            mapf_solver: MapfSolver::Cbs,
+            mapf: None,
+            mapf_error: None,
+
+            dynamic_obstacles: Vec::new(),
+            pending_obstacle_route: Vec::new(),
+            obstacle_preview_time: 0,
+
+            show_distance_field: false,
+            clearance_weight: 0.0,
+            distance_cache: DistanceCache::default(),
+
+            smoothing: SmoothingOptions::default(),
+            smoothed_path: Vec::new(),
+
+            turn_penalties: TurnPenalties::default(),
+            show_heading_arrows: true,
+
+            lattice_radius: 2,
+            lattice_start_heading: None,
+            lattice_plan: None,
+            lattice_error: None,
+
+            hybrid_config: HybridConfig::default(),
+            hybrid_start_heading: Heading::ALL[0],
+            hybrid_goal_heading: Heading::ALL[0],
+            hybrid_plan: None,
+            hybrid_error: None,
+            show_hybrid_tree: true,
+
+            sampling_planner: SamplingPlanner::Rrt,
+            sampling_config: SamplingConfig::default(),
+            tree_run: None,
+            roadmap: None,
+            roadmap_path: None,
+            sampling_error: None,
+
+            potential_config: PotentialConfig::default(),
+            potential: None,
+            show_potential_field: true,
+
+            flow_field: None,
+            flow_agents: Vec::new(),
+            flow_playing: false,
+            flow_speed: 3.0,
+            show_flow_distance: false,
+
+            hpa_cluster_size: 10,
+            hierarchy: None,
+            hpa_result: None,
+            hpa_flat_expanded: None,
+            hpa_error: None,
+            show_hpa_overlay: true,
+            show_hpa_edges: false,
+
+            landmark_positions: Vec::new(),
+            landmark_count: 4,
+            landmarks: None,
+            use_alt: true,
+            alt_comparison: None,
+            shown_landmark_table: None,
+
+            visibility: None,
+            visibility_astar_length: None,
+            visibility_error: None,
+            show_visibility_edges: true,
+            show_visibility_polygons: true,
+
+            current_floor: 0,
+
             ui: UIState::default(),
             theme: Theme::default(),
         }
@@ -159,22 +444,198 @@ impl RoboNav {
         self.is_solving = false;
         self.solving_step = 0;
         self.pathfinding_state = None;
+        self.comparison = None;
+        self.exploration = None;
+        self.frontier_explorer = None;
+        self.coverage = None;
+        self.tour = None;
+        self.tour_error = None;
+        self.mapf = None;
+        self.mapf_error = None;
+        self.lattice_plan = None;
+        self.lattice_error = None;
+        self.hybrid_plan = None;
+        self.hybrid_error = None;
+        self.tree_run = None;
+        self.roadmap_path = None;
+        self.sampling_error = None;
+        self.potential = None;
+        self.flow_field = None;
+        self.flow_playing = false;
+        self.hpa_result = None;
+        self.hpa_flat_expanded = None;
+        self.hpa_error = None;
+        self.visibility = None;
+        self.visibility_astar_length = None;
+        self.visibility_error = None;
         self.final_path.clear();
-        // self.robot_pos = self.start_pos;
+        self.smoothed_path.clear();
+        self.robot.clear();
     }
 
     fn clear_all_obstacles(&mut self) {
-        for i in 0..self.grid.width {
-            for j in 0..self.grid.height {
-                let pos = Position {
-                    x: i as i32,
-                    y: j as i32,
-                };
-                if self.grid.get_cell(&pos) == CellType::Obstacle {
-                    self.grid.set_cell(pos, CellType::Empty);
-                }
+        self.grid.clear_obstacles();
+        self.hierarchy = None;
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        self.clear_visualization();
+        self.grid.topology = topology;
+        // Re-inflate with the new neighbourhood
+        self.grid.set_robot_radius(self.grid.robot_radius());
+        self.hierarchy = None;
+        self.roadmap = None;
+        self.landmarks = None;
+        self.flow_agents.clear();
+        self.apply_grid_limits();
+    }
+
+    fn add_floor(&mut self) {
+        self.clear_visualization();
+        self.grid.add_floor();
+        self.current_floor = self.grid.floor_count() as i32 - 1;
+        self.apply_grid_limits();
+    }
+
+    /// Removes the top floor along with the endpoints and waypoints on it.
+    fn remove_top_floor(&mut self) {
+        self.clear_visualization();
+        self.grid.remove_top_floor();
+        let floors = self.grid.floor_count() as i32;
+        for endpoint in [&mut self.start_pos, &mut self.goal_pos] {
+            if endpoint.is_some_and(|pos| pos.floor >= floors) {
+                *endpoint = None;
             }
         }
+        self.waypoints.retain(|pos| pos.floor < floors);
+        self.apply_grid_limits();
+    }
+
+    /// Tools for features that need a single floor, or several.
+    fn tool_available(&self, tool: Tool) -> bool {
+        let single_floor = self.grid.floor_count() == 1;
+        match tool {
+            Tool::PlaceAgent
+            | Tool::AddMovingObstacle
+            | Tool::PlaceLandmark
+            | Tool::SpawnFlowAgent => single_floor,
+            Tool::PlaceStairs | Tool::PlaceElevator => !single_floor,
+            _ => true,
+        }
+    }
+
+    /// Falls back from algorithms and tools the grid's cells or floors do
+    /// not support, and drops state that only exists on single-floor maps.
+    fn apply_grid_limits(&mut self) {
+        if !self.current_algorithm.supports(&self.grid) {
+            self.current_algorithm = Algorithm::AStar;
+        }
+        let grid = &self.grid;
+        self.comparison_algorithms.retain(|a| a.supports(grid));
+        if !self.tool_available(self.selected_tool) {
+            self.selected_tool = Tool::SetStart;
+        }
+        self.current_floor = self
+            .current_floor
+            .clamp(0, self.grid.floor_count() as i32 - 1);
+        if self.grid.floor_count() > 1 {
+            self.hierarchy = None;
+            self.roadmap = None;
+            self.landmarks = None;
+            self.landmark_positions.clear();
+            self.agents.clear();
+            self.pending_agent_start = None;
+            self.dynamic_obstacles.clear();
+            self.pending_obstacle_route.clear();
+            self.flow_agents.clear();
+        }
+    }
+
+    fn resize_grid(&mut self) {
+        self.clear_visualization();
+        self.hierarchy = None;
+        let offset = self.grid.resize(
+            self.resize_width.max(1),
+            self.resize_height.max(1),
+            self.resize_anchor,
+        );
+        self.shift_endpoints(offset);
+        self.selection = None;
+    }
+
+    fn crop_to_selection(&mut self) {
+        if let Some((a, b)) = self.selection.take() {
+            self.clear_visualization();
+            self.hierarchy = None;
+            let offset = self.grid.crop(a, b);
+            self.shift_endpoints(offset);
+            self.resize_width = self.grid.width;
+            self.resize_height = self.grid.height;
+        }
+    }
+
+    /// Moves start/goal along with the grid content. An endpoint that falls
+    /// outside moves to the nearest empty cell, or is dropped when
+    /// `clamp_endpoints` is off or no such cell is left.
+    fn shift_endpoints(&mut self, offset: Position) {
+        let shift = |p: Position| Position::on_floor(p.x + offset.x, p.y + offset.y, p.floor);
+        for (endpoint, cell_type) in [
+            (&mut self.start_pos, CellType::Start),
+            (&mut self.goal_pos, CellType::Goal),
+        ] {
+            let Some(pos) = *endpoint else { continue };
+            let moved = shift(pos);
+            *endpoint = if self.grid.is_valid_position(&moved) {
+                Some(moved)
+            } else if self.clamp_endpoints
+                && let Some(clamped) = self.grid.nearest_empty_cell(moved)
+            {
+                self.grid.set_cell(clamped, cell_type);
+                Some(clamped)
+            } else {
+                None
+            };
+        }
+
+        let (width, height) = (self.grid.width as i32, self.grid.height as i32);
+        self.waypoints = self
+            .waypoints
+            .iter()
+            .map(|p| shift(*p))
+            .filter(|p| p.x >= 0 && p.x < width && p.y >= 0 && p.y < height)
+            .collect();
+        self.landmark_positions = self
+            .landmark_positions
+            .iter()
+            .map(|p| shift(*p))
+            .filter(|p| p.x >= 0 && p.x < width && p.y >= 0 && p.y < height)
+            .collect();
+        self.landmarks = None;
+        self.agents = self
+            .agents
+            .iter()
+            .map(|agent| Agent {
+                start: shift(agent.start),
+                goal: shift(agent.goal),
+            })
+            .filter(|agent| {
+                self.grid.is_valid_position(&agent.start)
+                    && self.grid.is_valid_position(&agent.goal)
+            })
+            .collect();
+        self.pending_agent_start = None;
+        self.dynamic_obstacles = self
+            .dynamic_obstacles
+            .iter()
+            .map(|obstacle| obstacle.shifted(offset))
+            .filter(|obstacle| {
+                obstacle
+                    .route()
+                    .iter()
+                    .all(|p| self.grid.is_valid_position(p))
+            })
+            .collect();
+        self.pending_obstacle_route.clear();
     }
 
     fn frontier_len(&self) -> usize {
@@ -189,8 +650,26 @@ impl RoboNav {
         self.clear_visualization();
 
         if let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) {
+            if self.comparison_mode {
+                self.comparison = Some(Comparison::new(
+                    &self.grid,
+                    &self.comparison_algorithms,
+                    start,
+                    goal,
+                    &self.dynamic_obstacles,
+                    self.clearance_weight,
+                    self.turn_penalties,
+                ));
+                self.is_solving = true;
+                return;
+            }
+
             let mut state = PathfindingState::new();
-            state.initialize(&self.current_algorithm, start, goal);
+            state.initialize(&self.current_algorithm, self.grid.topology, start, goal);
+            state.set_dynamic_obstacles(&self.dynamic_obstacles);
+            state.set_clearance_weight(&self.grid, self.clearance_weight);
+            state.set_turn_penalties(self.turn_penalties);
+            state.set_landmarks(self.alt_landmarks());
             self.pathfinding_state = Some(state);
             self.is_solving = true;
             self.algorithm_info = self.current_algorithm.description().to_string();
@@ -198,6 +677,14 @@ impl RoboNav {
     }
 
     fn step_pathfinding(&mut self) -> bool {
+        if self.is_solving
+            && let Some(comparison) = &mut self.comparison
+        {
+            let finished = comparison.step();
+            self.is_solving = !finished;
+            return finished;
+        }
+
         if !self.is_solving || self.pathfinding_state.is_none() {
             return false;
         }
@@ -211,8 +698,10 @@ impl RoboNav {
             pathfinding_state::StepResult::Continue => false,
             pathfinding_state::StepResult::PathFound(path) => {
                 self.final_path = path;
+                self.update_smoothed_path();
                 self.grid
                     .mark_path(&self.final_path, self.start_pos, self.goal_pos);
+                self.robot.set_path(self.final_path.clone());
                 self.is_solving = false;
                 true
             }
@@ -223,6 +712,428 @@ impl RoboNav {
         }
     }
 
+    fn update_smoothed_path(&mut self) {
+        // Smoothing works in square-cell geometry on a single floor
+        if self.grid.topology.is_hex() || self.grid.floor_count() > 1 {
+            self.smoothed_path.clear();
+            return;
+        }
+        self.smoothed_path = smoothing::smooth_path(&self.grid, &self.final_path, &self.smoothing);
+    }
+
+    fn simulations_running(&self) -> bool {
+        self.exploration.as_ref().is_some_and(SensorSim::is_running)
+            || self
+                .frontier_explorer
+                .as_ref()
+                .is_some_and(FrontierExplorer::is_running)
+            || self.coverage.as_ref().is_some_and(CoverageRun::is_running)
+            || self.mapf.as_ref().is_some_and(MapfRun::is_running)
+            || self.tree_run.as_ref().is_some_and(TreeRun::is_running)
+            || self.roadmap.as_ref().is_some_and(Roadmap::is_running)
+            || self
+                .potential
+                .as_ref()
+                .is_some_and(PotentialRun::is_running)
+    }
+
+    fn step_simulations(&mut self) {
+        if let Some(sim) = &mut self.exploration {
+            sim.step(&self.grid);
+        }
+        if let Some(explorer) = &mut self.frontier_explorer {
+            explorer.step(&self.grid);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.step(&mut self.grid);
+        }
+        if let Some(run) = &mut self.mapf {
+            run.step();
+        }
+        if let Some(run) = &mut self.tree_run {
+            run.step(&self.grid);
+        }
+        if let Some(run) = &mut self.potential {
+            run.step(&self.grid);
+        }
+        if let Some(roadmap) = &mut self.roadmap
+            && roadmap.is_running()
+        {
+            roadmap.step(&self.grid);
+            if !roadmap.is_running() {
+                self.query_roadmap();
+            }
+        }
+    }
+
+    /// Landmarks for A*, when ALT is enabled and tables exist.
+    fn alt_landmarks(&self) -> Option<Rc<Landmarks>> {
+        self.landmarks.clone().filter(|_| self.use_alt)
+    }
+
+    fn compute_landmarks(&mut self) {
+        self.shown_landmark_table = None;
+        self.landmarks = (!self.landmark_positions.is_empty()).then(|| {
+            Rc::new(Landmarks::compute(
+                &self.grid,
+                self.landmark_positions.clone(),
+            ))
+        });
+        self.compare_alt();
+    }
+
+    fn select_farthest_landmarks(&mut self) {
+        let seed = self
+            .start_pos
+            .filter(|p| self.grid.is_walkable(p))
+            .or_else(|| {
+                (0..self.grid.height as i32)
+                    .flat_map(|y| (0..self.grid.width as i32).map(move |x| Position::new(x, y)))
+                    .find(|pos| self.grid.is_walkable(pos))
+            });
+        let Some(seed) = seed else {
+            return;
+        };
+        let landmarks = Landmarks::farthest(&self.grid, seed, self.landmark_count);
+        self.landmark_positions = landmarks.positions.clone();
+        self.landmarks = Some(Rc::new(landmarks));
+        self.shown_landmark_table = None;
+        self.compare_alt();
+    }
+
+    /// Runs A* between start and goal with both heuristics on scratch grids.
+    fn compare_alt(&mut self) {
+        self.alt_comparison = None;
+        let (Some(start), Some(goal), Some(landmarks)) =
+            (self.start_pos, self.goal_pos, &self.landmarks)
+        else {
+            return;
+        };
+        let expanded = |landmarks: Option<Rc<Landmarks>>| {
+            let mut scratch = self.grid.clone();
+            let mut state = PathfindingState::new();
+            state.initialize(&Algorithm::AStar, self.grid.topology, start, goal);
+            state.set_landmarks(landmarks);
+            state.run(&Algorithm::AStar, goal, &mut scratch);
+            state.closed_set_len()
+        };
+        self.alt_comparison = Some((expanded(Some(landmarks.clone())), expanded(None)));
+    }
+
+    fn build_hierarchy(&mut self) {
+        self.hierarchy = Some(Hierarchy::build(&self.grid, self.hpa_cluster_size));
+    }
+
+    /// Patches the cluster graph after `pos` changed, including every cell
+    /// whose inflation the change may have affected.
+    fn update_hierarchy(&mut self, pos: Position) {
+        if let Some(hierarchy) = &mut self.hierarchy {
+            let reach = self.grid.robot_radius().ceil() as i32;
+            hierarchy.update_region(
+                &self.grid,
+                Position::new(pos.x - reach, pos.y - reach),
+                Position::new(pos.x + reach, pos.y + reach),
+            );
+        }
+    }
+
+    fn plan_hpa(&mut self) {
+        self.clear_visualization();
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            self.hpa_error = Some("Place a start and goal first".to_string());
+            return;
+        };
+        if self
+            .hierarchy
+            .as_ref()
+            .is_none_or(|h| !h.fits(&self.grid) || h.cluster_size != self.hpa_cluster_size)
+        {
+            self.build_hierarchy();
+        }
+        let hierarchy = self.hierarchy.as_ref().unwrap();
+        let Some(result) = hierarchy.find_path(&self.grid, start, goal) else {
+            self.hpa_error = Some("No path between start and goal".to_string());
+            return;
+        };
+
+        // Flat A* on a scratch copy, for comparison
+        let mut scratch = self.grid.clone();
+        let mut state = PathfindingState::new();
+        state.initialize(&Algorithm::AStar, self.grid.topology, start, goal);
+        state.run(&Algorithm::AStar, goal, &mut scratch);
+        self.hpa_flat_expanded = Some(state.closed_set_len());
+
+        self.final_path = result.path.clone();
+        self.update_smoothed_path();
+        self.grid
+            .mark_path(&self.final_path, self.start_pos, self.goal_pos);
+        self.robot.set_path(self.final_path.clone());
+        self.hpa_result = Some(result);
+        self.algorithm_info = "HPA* splits the map into clusters and places transition cells on the entrances between neighbouring clusters. Distances between transitions inside each cluster are cached, so a query only searches the small abstract graph and then refines each abstract edge inside a single cluster.".to_string();
+    }
+
+    fn plan_visibility(&mut self) {
+        self.clear_visualization();
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            self.visibility_error = Some("Place a start and goal first".to_string());
+            return;
+        };
+        let graph = VisibilityGraph::build(&self.grid, start, goal);
+        if graph.path.is_none() {
+            self.visibility_error = Some("No path between start and goal".to_string());
+        }
+        self.visibility = Some(graph);
+
+        // Grid A* on a scratch copy, for comparison
+        let mut scratch = self.grid.clone();
+        let mut state = PathfindingState::new();
+        state.initialize(&Algorithm::AStar, self.grid.topology, start, goal);
+        self.visibility_astar_length = state
+            .run(&Algorithm::AStar, goal, &mut scratch)
+            .map(|path| path.len() - 1);
+        self.algorithm_info = "The visibility graph joins every pair of convex obstacle corners that can see each other, plus the start and goal. The shortest path among polygonal obstacles only ever bends at such corners, so Dijkstra on this graph gives the exact Euclidean shortest path, a reference for how much grid-constrained paths lose.".to_string();
+    }
+
+    /// Recomputes the flow field when there is none, the goal moved or the
+    /// cell shape changed.
+    fn ensure_flow_field(&mut self) -> bool {
+        let Some(goal) = self.goal_pos else {
+            self.flow_field = None;
+            return false;
+        };
+        if self
+            .flow_field
+            .as_ref()
+            .is_none_or(|field| field.goal != goal || field.topology != self.grid.topology)
+        {
+            self.flow_field = Some(FlowField::compute(&self.grid, goal));
+        }
+        true
+    }
+
+    fn spawn_flow_agent(&mut self, cell: Position) {
+        // Spread agents dropped on the same cell so they stay visible
+        let k = self.flow_agents.len() as f32;
+        let jitter = egui::vec2((k * 0.618).fract() - 0.5, (k * 0.382).fract() - 0.5) * 0.6;
+        let position = smoothing::cell_center(&cell) + jitter;
+        self.flow_agents.push(FlowAgent::new(position));
+    }
+
+    /// Drops agents on evenly spread free cells (golden-ratio sequence).
+    fn scatter_flow_agents(&mut self, count: usize) {
+        let free: Vec<Position> = (0..self.grid.height as i32)
+            .flat_map(|y| (0..self.grid.width as i32).map(move |x| Position::new(x, y)))
+            .filter(|pos| self.grid.is_walkable(pos))
+            .collect();
+        if free.is_empty() {
+            return;
+        }
+        let offset = self.flow_agents.len();
+        for i in 0..count {
+            let t = ((offset + i) as f32 * 0.618_034).fract();
+            self.spawn_flow_agent(free[(t * free.len() as f32) as usize]);
+        }
+    }
+
+    fn update_flow_agents(&mut self, dt: f32) {
+        if !self.ensure_flow_field() {
+            self.flow_playing = false;
+            return;
+        }
+        let field = self.flow_field.as_ref().unwrap();
+        for agent in &mut self.flow_agents {
+            agent.advance(field, self.flow_speed * dt);
+        }
+        if self
+            .flow_agents
+            .iter()
+            .all(|agent| agent.arrived || agent.is_stranded(field))
+        {
+            self.flow_playing = false;
+        }
+    }
+
+    fn start_potential_descent(&mut self) {
+        self.clear_visualization();
+        if let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) {
+            self.potential = Some(PotentialRun::new(start, goal, self.potential_config));
+            self.algorithm_info = "Artificial potential fields pull the robot towards the goal and push it away from nearby obstacles; the robot follows the combined force downhill. Where attraction and repulsion cancel, for example inside a U-shaped wall facing the goal, it gets trapped in a local minimum.".to_string();
+        }
+    }
+
+    fn start_tree_run(&mut self) {
+        self.clear_visualization();
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            self.sampling_error = Some("Place a start and goal first".to_string());
+            return;
+        };
+        let star = self.sampling_planner == SamplingPlanner::RrtStar;
+        self.tree_run = Some(TreeRun::new(start, goal, star, self.sampling_config));
+        self.algorithm_info = self.sampling_planner.description().to_string();
+    }
+
+    fn build_roadmap(&mut self) {
+        self.clear_visualization();
+        self.roadmap = Some(Roadmap::new(&self.grid, self.sampling_config));
+        self.algorithm_info = SamplingPlanner::Prm.description().to_string();
+    }
+
+    fn query_roadmap(&mut self) {
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            return;
+        };
+        let Some(roadmap) = &mut self.roadmap else {
+            return;
+        };
+        self.roadmap_path = roadmap.query(&self.grid, start, goal);
+        self.sampling_error = self
+            .roadmap_path
+            .is_none()
+            .then(|| "Start and goal are not connected by the roadmap".to_string());
+    }
+
+    fn start_coverage(&mut self) {
+        self.clear_visualization();
+        if let Some(start) = self.start_pos {
+            self.coverage = Some(CoverageRun::new(&self.grid, start, self.coverage_pattern));
+            self.algorithm_info = self.coverage_pattern.description().to_string();
+        }
+    }
+
+    fn start_exploration(&mut self) {
+        self.clear_visualization();
+        if let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) {
+            self.exploration = Some(SensorSim::new(
+                &self.grid,
+                start,
+                goal,
+                self.sensor_radius,
+                self.unknown_policy,
+            ));
+        }
+    }
+
+    fn start_frontier_exploration(&mut self) {
+        self.clear_visualization();
+        if let Some(start) = self.start_pos {
+            self.frontier_explorer =
+                Some(FrontierExplorer::new(&self.grid, start, self.sensor_radius));
+        }
+    }
+
+    fn plan_tour(&mut self) {
+        self.clear_visualization();
+        let Some(start) = self.start_pos else {
+            self.tour_error = Some("Place a start position first".to_string());
+            return;
+        };
+
+        match routing::plan_tour(
+            &self.grid,
+            start,
+            &self.waypoints,
+            self.goal_pos,
+            self.waypoints_ordered,
+        ) {
+            Ok(tour) => {
+                self.final_path = tour.path();
+                self.update_smoothed_path();
+                self.grid
+                    .mark_path(&self.final_path, self.start_pos, self.goal_pos);
+                self.robot.set_path(self.final_path.clone());
+                self.tour = Some(tour);
+            }
+            Err(RoutingError::Unreachable(from, to)) => {
+                self.tour_error = Some(format!(
+                    "No path from ({}, {}) to ({}, {})",
+                    from.x, from.y, to.x, to.y
+                ));
+            }
+        }
+    }
+
+    fn plan_lattice(&mut self) {
+        self.clear_visualization();
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            self.lattice_error = Some("Place a start and goal first".to_string());
+            return;
+        };
+        let lattice = Lattice::new(self.lattice_radius);
+        match lattice::plan(
+            &self.grid,
+            &lattice,
+            start,
+            self.lattice_start_heading,
+            goal,
+        ) {
+            Ok(plan) => {
+                self.grid
+                    .mark_path(&plan.swept, self.start_pos, self.goal_pos);
+                self.lattice_plan = Some(plan);
+            }
+            Err(LatticeError::NoPath(expanded)) => {
+                self.lattice_error = Some(format!(
+                    "No drivable path with turning radius {} ({} states expanded)",
+                    self.lattice_radius, expanded
+                ));
+            }
+            Err(LatticeError::ExpansionLimit) => {
+                self.lattice_error = Some("Expansion limit reached".to_string());
+            }
+        }
+    }
+
+    fn plan_hybrid(&mut self) {
+        self.clear_visualization();
+        let (Some(start), Some(goal)) = (self.start_pos, self.goal_pos) else {
+            self.hybrid_error = Some("Place a start and goal first".to_string());
+            return;
+        };
+        let start = Pose::at_cell(start, self.hybrid_start_heading.angle());
+        let goal = Pose::at_cell(goal, self.hybrid_goal_heading.angle());
+        match hybrid_astar::plan(&self.grid, start, goal, &self.hybrid_config) {
+            Ok(plan) => self.hybrid_plan = Some(plan),
+            Err(HybridError::StartBlocked) => {
+                self.hybrid_error = Some("Start pose is blocked".to_string());
+            }
+            Err(HybridError::GoalBlocked) => {
+                self.hybrid_error = Some("Goal pose is blocked".to_string());
+            }
+            Err(HybridError::NoPath(expanded)) => {
+                self.hybrid_error = Some(format!("No path ({expanded} nodes expanded)"));
+            }
+            Err(HybridError::ExpansionLimit) => {
+                self.hybrid_error = Some("Expansion limit reached".to_string());
+            }
+        }
+    }
+
+    fn solve_mapf(&mut self) {
+        self.clear_visualization();
+        match mapf::solve(&self.grid, &self.agents, self.mapf_solver) {
+            Ok(solution) => {
+                self.mapf = Some(MapfRun {
+                    solver: self.mapf_solver,
+                    solution,
+                    time: 0,
+                });
+                self.algorithm_info = self.mapf_solver.description().to_string();
+            }
+            Err(MapfError::NoPath(agent)) => {
+                self.mapf_error = Some(format!("No path found for agent {}", agent + 1));
+            }
+            Err(MapfError::SharedStart(a, b)) => {
+                self.mapf_error = Some(format!("Agents {} and {} share a start", a + 1, b + 1));
+            }
+            Err(MapfError::SharedGoal(a, b)) => {
+                self.mapf_error = Some(format!("Agents {} and {} share a goal", a + 1, b + 1));
+            }
+            Err(MapfError::NodeLimit) => {
+                self.mapf_error = Some("CBS gave up: too many conflicts to resolve".to_string());
+            }
+        }
+    }
+
     fn handle_grid_click(&mut self, pos: Position) {
         if !self.grid.is_valid_position(&pos) {
             return;
@@ -236,8 +1147,8 @@ impl RoboNav {
                     self.grid.set_cell(old_start, CellType::Empty);
                 }
                 self.start_pos = Some(pos);
-                // self.robot_pos = Some(pos);
                 self.grid.set_cell(pos, CellType::Start);
+                self.update_hierarchy(pos);
             }
             Tool::SetGoal => {
                 if let Some(old_goal) = self.goal_pos
@@ -247,15 +1158,110 @@ impl RoboNav {
                 }
                 self.goal_pos = Some(pos);
                 self.grid.set_cell(pos, CellType::Goal);
+                self.update_hierarchy(pos);
             }
             Tool::AddObstacle => {
                 if self.grid.get_cell(&pos) == CellType::Empty {
                     self.grid.set_cell(pos, CellType::Obstacle);
+                    self.update_hierarchy(pos);
                 }
             }
             Tool::RemoveObstacle => {
                 if self.grid.get_cell(&pos) == CellType::Obstacle {
                     self.grid.set_cell(pos, CellType::Empty);
+                    self.update_hierarchy(pos);
+                }
+            }
+            Tool::SelectArea => {
+                self.selection = Some((pos, pos));
+            }
+            Tool::PlaceAgent => {
+                if !self.grid.is_walkable(&pos) {
+                    return;
+                }
+                // First click places the agent's start, the second its goal;
+                // starts and goals must each be distinct between agents
+                match self.pending_agent_start {
+                    Some(start) => {
+                        if self.agents.iter().any(|agent| agent.goal == pos) {
+                            self.mapf_error =
+                                Some("Another agent already has that goal".to_string());
+                            return;
+                        }
+                        self.pending_agent_start = None;
+                        self.agents.push(Agent { start, goal: pos });
+                        self.mapf = None;
+                        self.mapf_error = None;
+                    }
+                    None => {
+                        if self.agents.iter().any(|agent| agent.start == pos) {
+                            self.mapf_error =
+                                Some("Another agent already starts there".to_string());
+                            return;
+                        }
+                        self.pending_agent_start = Some(pos);
+                        self.mapf_error = None;
+                    }
+                }
+            }
+            Tool::AddMovingObstacle => {
+                if self.grid.is_walkable(&pos) {
+                    self.pending_obstacle_route.push(pos);
+                }
+            }
+            Tool::PlaceLandmark => {
+                if let Some(index) = self.landmark_positions.iter().position(|l| *l == pos) {
+                    self.landmark_positions.remove(index);
+                } else if self.grid.is_walkable(&pos) {
+                    self.landmark_positions.push(pos);
+                }
+                self.compute_landmarks();
+            }
+            Tool::SpawnFlowAgent => {
+                if self.grid.is_walkable(&pos) {
+                    self.spawn_flow_agent(pos);
+                }
+            }
+            Tool::AddWaypoint => {
+                if let Some(index) = self.waypoints.iter().position(|w| *w == pos) {
+                    self.waypoints.remove(index);
+                } else if self.grid.is_walkable(&pos) {
+                    self.waypoints.push(pos);
+                }
+            }
+            Tool::PlaceStairs => {
+                // Stairs link this floor with the one above, or the one
+                // below from the top floor, and need a free cell on both
+                if self.grid.connector(&pos).is_some() {
+                    self.grid.remove_connector(pos);
+                } else {
+                    let floor = if pos.floor + 1 < self.grid.floor_count() as i32 {
+                        pos.floor + 1
+                    } else {
+                        pos.floor - 1
+                    };
+                    let partner = Position::on_floor(pos.x, pos.y, floor);
+                    if self.grid.get_cell(&pos) != CellType::Obstacle
+                        && self.grid.get_cell(&partner) != CellType::Obstacle
+                    {
+                        self.grid.set_connector(pos, Some(Connector::Stairs));
+                        self.grid.set_connector(partner, Some(Connector::Stairs));
+                    }
+                }
+            }
+            Tool::PlaceElevator => {
+                // A new shaft gets a door on every floor without a wall
+                // there; clicking a door removes it so the elevator skips
+                // that floor
+                if self.grid.connector(&pos).is_some() {
+                    self.grid.remove_connector(pos);
+                } else if self.grid.get_cell(&pos) != CellType::Obstacle {
+                    for floor in 0..self.grid.floor_count() as i32 {
+                        self.grid.set_connector(
+                            Position::on_floor(pos.x, pos.y, floor),
+                            Some(Connector::Elevator),
+                        );
+                    }
                 }
             }
         }
@@ -265,15 +1271,35 @@ impl RoboNav {
 impl eframe::App for RoboNav {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Auto-stepping
-        if self.is_solving && !self.step_by_step {
+        if (self.is_solving || self.simulations_running()) && !self.step_by_step {
             let current_time = ctx.input(|i| i.time);
             if current_time - self.last_step_time > self.auto_solve_speed as f64 {
-                self.step_pathfinding();
+                if self.is_solving {
+                    self.step_pathfinding();
+                }
+                self.step_simulations();
                 self.last_step_time = current_time;
             }
             ctx.request_repaint();
         }
 
+        if self.robot.playing {
+            let floor = self.robot.floor();
+            self.robot.update(ctx.input(|i| i.stable_dt));
+            // Follow the robot when it changes floors
+            if let Some(next_floor) = self.robot.floor()
+                && Some(next_floor) != floor
+            {
+                self.current_floor = next_floor;
+            }
+            ctx.request_repaint();
+        }
+
+        if self.flow_playing {
+            self.update_flow_agents(ctx.input(|i| i.stable_dt));
+            ctx.request_repaint();
+        }
+
         ctx.set_style(self.theme.style());
         self.render_ui(ctx);
     }
@@ -333,25 +1359,23 @@ impl RoboNav {
                     ui.group(|ui| {
                         ui.label("Algorithm:");
                         egui::ComboBox::from_label("")
-                            .selected_text(format!("{:?}", self.current_algorithm))
+                            .selected_text(self.current_algorithm.name())
                             .width(100.0)
                             .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    &mut self.current_algorithm,
-                                    Algorithm::Bfs,
-                                    "BFS",
-                                );
-                                ui.selectable_value(
-                                    &mut self.current_algorithm,
-                                    Algorithm::Dfs,
-                                    "DFS",
-                                );
-                                ui.selectable_value(
-                                    &mut self.current_algorithm,
-                                    Algorithm::AStar,
-                                    "A*",
-                                );
+                                for algorithm in Algorithm::ALL
+                                    .into_iter()
+                                    .filter(|a| a.supports(&self.grid))
+                                {
+                                    ui.selectable_value(
+                                        &mut self.current_algorithm,
+                                        algorithm,
+                                        algorithm.name(),
+                                    );
+                                }
                             });
+                        if ui.checkbox(&mut self.comparison_mode, "Compare").changed() {
+                            self.clear_visualization();
+                        }
                     });
 
                     // Control buttons
@@ -398,6 +1422,28 @@ impl RoboNav {
                                 Tool::RemoveObstacle,
                                 "⬜ Remove Wall",
                             );
+                            ui.selectable_value(
+                                &mut self.selected_tool,
+                                Tool::SelectArea,
+                                "⬚ Select",
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_tool,
+                                Tool::AddWaypoint,
+                                "📍 Waypoint",
+                            );
+                            for (tool, label) in [
+                                (Tool::PlaceAgent, "🚚 Agent"),
+                                (Tool::AddMovingObstacle, "🚧 Moving Obstacle"),
+                                (Tool::SpawnFlowAgent, "🐜 Flow Agent"),
+                                (Tool::PlaceLandmark, "⭐ Landmark"),
+                                (Tool::PlaceStairs, "⇅ Stairs"),
+                                (Tool::PlaceElevator, "🔼 Elevator"),
+                            ] {
+                                if self.tool_available(tool) {
+                                    ui.selectable_value(&mut self.selected_tool, tool, label);
+                                }
+                            }
 
                             let clear_all_obstacles_button =
                                 egui::Button::new("🗑 Remove All Obstacles")
@@ -416,7 +1462,14 @@ impl RoboNav {
                             if ui.add(load_map_button).clicked()
                                 && let Ok(grid) = map_handler::load_map()
                             {
+                                self.resize_width = grid.width;
+                                self.resize_height = grid.height;
+                                self.selection = None;
+                                let radius = self.grid.robot_radius();
                                 self.grid = grid;
+                                self.grid.set_robot_radius(radius);
+                                self.hierarchy = None;
+                                self.apply_grid_limits();
                             }
                             let save_map_button =
                                 egui::Button::new("Save map").min_size(egui::vec2(50.0, 30.0));
@@ -433,11 +1486,19 @@ impl RoboNav {
 
     fn render_main_content(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
+            if self.show_distance_field {
+                // Fill the cache before the legend reads its range
+                self.distance_cache.get(&self.grid, self.current_floor);
+            }
             ui.horizontal(|ui| {
                 ui.vertical(|ui| {
                     self.render_legend(ui);
                     ui.add_space(10.0);
-                    self.render_grid(ui);
+                    if self.comparison.is_some() {
+                        self.render_comparison(ui);
+                    } else {
+                        self.render_grid(ui);
+                    }
                 });
             });
         });
@@ -448,95 +1509,276 @@ impl RoboNav {
             .min_width(300.0)
             .max_width(400.0)
             .show(ctx, |ui| {
-                egui::CollapsingHeader::new("⚙ Display Settings")
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        ui.add(
-                            egui::Slider::new(&mut self.grid.size, 5.0..=50.0)
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::CollapsingHeader::new("⚙ Display Settings")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.grid.size,
+                                    viewport::MIN_CELL_SIZE..=viewport::MAX_CELL_SIZE,
+                                )
+                                .logarithmic(true)
                                 .text("Cell size")
                                 .show_value(false),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.show_minimap, "Show Minimap");
+                                if ui.button("Reset View").clicked() {
+                                    self.pan_offset = egui::Vec2::ZERO;
+                                }
+                            });
+                            ui.label(
+                                egui::RichText::new("Scroll to zoom, middle-drag to pan")
+                                    .size(10.0)
+                                    .italics(),
+                            );
+                            ui.separator();
+                            ui.checkbox(&mut self.show_heuristics, "Show Heuristics (h)");
+                            ui.checkbox(&mut self.show_costs, "Show Costs (g/f)");
+                            ui.checkbox(&mut self.show_parent_arrows, "Show Parent Arrows");
+                            ui.checkbox(&mut self.show_visit_order, "Show Visit Order");
+                            if self.show_visit_order {
+                                ui.horizontal(|ui| {
+                                    ui.selectable_value(
+                                        &mut self.visit_order_style,
+                                        VisitOrderStyle::Heatmap,
+                                        "Heatmap",
+                                    );
+                                    ui.selectable_value(
+                                        &mut self.visit_order_style,
+                                        VisitOrderStyle::Numbers,
+                                        "Numbers",
+                                    );
+                                });
+                            }
+
+                            ui.separator();
+                            ui.checkbox(&mut self.step_by_step, "Step-by-Step Mode");
+
+                            if !self.step_by_step {
+                                ui.add(
+                                    egui::Slider::new(&mut self.auto_solve_speed, 0.0..=2.0)
+                                        .text("Time Delay (s)")
+                                        .show_value(true),
+                                );
+                            }
+                        });
+                    ui.separator();
+
+                    self.render_grid_size_settings(ui);
+                    ui.separator();
+
+                    self.render_floor_controls(ui);
+                    ui.separator();
+
+                    if self.comparison_mode {
+                        self.render_comparison_settings(ui);
+                        ui.separator();
+                    }
+
+                    self.render_robot_controls(ui);
+                    ui.separator();
+
+                    let single_floor = self.grid.floor_count() == 1;
+                    if !single_floor {
+                        ui.label(
+                            egui::RichText::new(
+                                "Exploration, coverage, multi-agent, moving obstacle, landmark and continuous planners work on one floor and are hidden on multi-floor maps",
+                            )
+                            .size(10.0)
+                            .italics(),
                         );
                         ui.separator();
-                        ui.checkbox(&mut self.show_heuristics, "Show Heuristics (h)");
-                        ui.checkbox(&mut self.show_costs, "Show Costs (g/f)");
-                        ui.checkbox(&mut self.show_parent_arrows, "Show Parent Arrows");
-                        // ui.checkbox(&mut self.show_visit_order, "Show Visit Order");
+                    }
 
+                    if single_floor {
+                        self.render_exploration_controls(ui);
                         ui.separator();
-                        ui.checkbox(&mut self.step_by_step, "Step-by-Step Mode");
 
-                        if !self.step_by_step {
-                            ui.add(
-                                egui::Slider::new(&mut self.auto_solve_speed, 0.0..=2.0)
-                                    .text("Time Delay (s)")
-                                    .show_value(true),
-                            );
-                        }
-                    });
-                ui.separator();
+                        self.render_coverage_controls(ui);
+                        ui.separator();
+                    }
 
-                // Statistics
-                if self.ui.show_statistics {
-                    self.render_statistics(ui);
+                    self.render_waypoint_controls(ui);
                     ui.separator();
-                }
 
-                // Inspector
-                if self.ui.show_inspector {
-                    self.render_inspector(ui);
-                }
+                    if single_floor {
+                        self.render_mapf_controls(ui);
+                        ui.separator();
+
+                        self.render_moving_obstacle_controls(ui);
+                        ui.separator();
+                    }
 
-                // Algorithm info
-                if !self.algorithm_info.is_empty() {
+                    self.render_clearance_controls(ui);
                     ui.separator();
-                    egui::CollapsingHeader::new("ℹ Algorithm Info")
-                        .default_open(false)
-                        .show(ui, |ui| {
-                            ui.label(self.algorithm_info.as_str());
-                        });
-                }
+
+                    if single_floor && self.grid.topology.is_hex() {
+                        ui.label(
+                            egui::RichText::new(
+                                "Smoothing, heading-aware, continuous and hierarchical planners assume square cells and are hidden on hex grids",
+                            )
+                            .size(10.0)
+                            .italics(),
+                        );
+                        ui.separator();
+                    } else if single_floor {
+                        self.render_smoothing_controls(ui);
+                        ui.separator();
+
+                        self.render_turning_controls(ui);
+                        ui.separator();
+
+                        self.render_lattice_controls(ui);
+                        ui.separator();
+
+                        self.render_hybrid_controls(ui);
+                        ui.separator();
+
+                        self.render_sampling_controls(ui);
+                        ui.separator();
+
+                        self.render_potential_controls(ui);
+                        ui.separator();
+
+                        self.render_hpa_controls(ui);
+                        ui.separator();
+                    }
+
+                    if single_floor {
+                        self.render_flow_field_controls(ui);
+                        ui.separator();
+
+                        self.render_landmark_controls(ui);
+                        ui.separator();
+                    }
+
+                    if single_floor && !self.grid.topology.is_hex() {
+                        self.render_visibility_controls(ui);
+                        ui.separator();
+                    }
+
+                    // Statistics
+                    if self.ui.show_statistics {
+                        self.render_statistics(ui);
+                        ui.separator();
+                    }
+
+                    // Inspector
+                    if self.ui.show_inspector {
+                        self.render_inspector(ui);
+                    }
+
+                    // Algorithm info
+                    if !self.algorithm_info.is_empty() {
+                        ui.separator();
+                        egui::CollapsingHeader::new("ℹ Algorithm Info")
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                ui.label(self.algorithm_info.as_str());
+                            });
+                    }
+                });
             });
     }
 
     fn render_grid(&mut self, ui: &mut egui::Ui) {
-        let grid_size = egui::Vec2::new(
-            self.grid.width as f32 * self.grid.size,
-            self.grid.height as f32 * self.grid.size,
-        );
-
-        let (response, painter) = ui.allocate_painter(grid_size, egui::Sense::click());
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
         let rect = response.rect;
+        let painter = painter.with_clip_rect(rect);
 
-        // Draw grid cells
-        let cell_size = self.grid.size;
-        for y in 0..self.grid.height {
-            for x in 0..self.grid.width {
-                let pos = Position::new(x as i32, y as i32);
-                let cell_rect = egui::Rect::from_min_size(
-                    rect.min + egui::Vec2::new(x as f32 * cell_size, y as f32 * cell_size),
-                    egui::Vec2::splat(cell_size),
-                );
+        self.handle_zoom_and_pan(ui, &response);
 
-                let mut cell_type = self.grid.get_cell(&pos);
+        let view = Viewport::new(
+            rect.min + self.pan_offset,
+            self.grid.size,
+            self.grid.topology,
+        )
+        .on_floor(self.current_floor);
+        let cell_size = view.cell_size;
+        let (xs, ys) = view.visible_cells(rect, self.grid.width, self.grid.height);
+        let show_text = cell_size >= 16.0;
 
-                // Override with start/goal positions
-                if Some(pos) == self.start_pos {
-                    cell_type = grid::CellType::Start;
-                } else if Some(pos) == self.goal_pos {
-                    cell_type = grid::CellType::Goal;
+        Self::paint_cells(
+            &painter,
+            &self.grid,
+            &view,
+            rect,
+            self.start_pos,
+            self.goal_pos,
+        );
+
+        if self.show_distance_field {
+            let (distance, max_distance) = self.distance_cache.get(&self.grid, view.floor);
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let d = distance[y as usize][x as usize];
+                    if d == 0.0 {
+                        continue;
+                    }
+                    let pos = view.cell(x, y);
+                    let cell_rect = view.cell_rect(&pos);
+                    let t = if d.is_finite() { d / max_distance } else { 1.0 };
+                    Self::fill_cell(
+                        &painter,
+                        &view,
+                        &pos,
+                        Theme::gradient(t).gamma_multiply(0.6),
+                    );
+                    if show_text && d.is_finite() {
+                        painter.text(
+                            cell_rect.center(),
+                            egui::Align2::CENTER_CENTER,
+                            format!("{d:.1}"),
+                            egui::FontId::monospace((cell_size * 0.3).min(10.0)),
+                            egui::Color32::BLACK,
+                        );
+                    }
                 }
+            }
+        }
 
-                let cell_color = cell_type.color();
+        if self.show_visit_order
+            && let Some(state) = &self.pathfinding_state
+        {
+            // First expansion maps to 0.0 and the last to 1.0
+            let span = state.step_count().saturating_sub(1).max(1) as f32;
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let pos = view.cell(x, y);
+                    let Some(order) = state.visit_order(&pos) else {
+                        continue;
+                    };
+                    let cell_rect = view.cell_rect(&pos);
+                    match self.visit_order_style {
+                        VisitOrderStyle::Heatmap => {
+                            let color = Theme::gradient((order - 1) as f32 / span);
+                            Self::fill_cell(&painter, &view, &pos, color.gamma_multiply(0.8));
+                        }
+                        VisitOrderStyle::Numbers if cell_size >= 12.0 => {
+                            painter.text(
+                                cell_rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                order.to_string(),
+                                egui::FontId::monospace((cell_size * 0.35).min(11.0)),
+                                egui::Color32::BLACK,
+                            );
+                        }
+                        VisitOrderStyle::Numbers => {}
+                    }
+                }
+            }
+        }
 
-                painter.rect_filled(cell_rect, 0.0, cell_color);
-                painter.rect_stroke(
-                    cell_rect,
-                    0.0,
-                    egui::Stroke::new(1.0, egui::Color32::from_rgb(162, 175, 155)),
-                    egui::StrokeKind::Middle,
-                );
+        for y in ys.clone() {
+            for x in xs.clone() {
+                let pos = view.cell(x, y);
+                let cell_rect = view.cell_rect(&pos);
 
-                if (self.show_heuristics || self.show_costs)
+                if show_text
+                    && (self.show_heuristics || self.show_costs)
                     && let Some(state) = &self.pathfinding_state
                 {
                     let mut text_lines = Vec::new();
@@ -574,16 +1816,15 @@ impl RoboNav {
             && let Some(state) = &self.pathfinding_state
         {
             for (parent, child) in state.came_from() {
-                let from = rect.min
-                    + egui::Vec2::new(
-                        child.x as f32 * cell_size + cell_size * 0.5,
-                        child.y as f32 * cell_size + cell_size * 0.5,
-                    );
-                let to = rect.min
-                    + egui::Vec2::new(
-                        parent.x as f32 * cell_size + cell_size * 0.5,
-                        parent.y as f32 * cell_size + cell_size * 0.5,
-                    );
+                if !xs.contains(&child.x)
+                    || !ys.contains(&child.y)
+                    || child.floor != view.floor
+                    || parent.floor != view.floor
+                {
+                    continue;
+                }
+                let from = view.cell_center(child);
+                let to = view.cell_center(parent);
 
                 // Arrow line
                 painter.line_segment(
@@ -605,99 +1846,2830 @@ impl RoboNav {
             }
         }
 
-        if response.clicked()
+        self.paint_heading_arrows(&painter, &view);
+        self.paint_smoothed_path(&painter, &view);
+        self.paint_lattice_plan(&painter, &view);
+        self.paint_hybrid_plan(&painter, &view);
+        self.paint_sampling(&painter, &view);
+        self.paint_potential(&painter, &view, xs.clone(), ys.clone());
+        self.paint_flow_field(&painter, &view, xs.clone(), ys.clone());
+        self.paint_hpa(&painter, &view, xs.clone(), ys.clone());
+        self.paint_landmarks(&painter, &view, xs.clone(), ys.clone());
+        self.paint_visibility(&painter, &view);
+        self.paint_floor_path(&painter, &view);
+        self.paint_waypoints(&painter, &view);
+        self.paint_agents(&painter, &view);
+        self.paint_moving_obstacles(&painter, &view);
+        self.paint_robot(&painter, &view);
+        self.paint_exploration(&painter, &view, xs.clone(), ys.clone());
+
+        if self.selected_tool == Tool::SelectArea
+            && let Some(pointer_pos) = response.interact_pointer_pos()
+        {
+            let cell = view.screen_to_cell(pointer_pos);
+            if response.drag_started_by(egui::PointerButton::Primary) {
+                self.selection = Some((cell, cell));
+            } else if response.dragged_by(egui::PointerButton::Primary)
+                && let Some((anchor, _)) = self.selection
+            {
+                self.selection = Some((anchor, cell));
+            }
+        }
+
+        if let Some((a, b)) = self.selection {
+            // A parallelogram on hex grids, since the cells are sheared
+            let (min_x, min_y) = (a.x.min(b.x) as f32, a.y.min(b.y) as f32);
+            let (max_x, max_y) = (a.x.max(b.x) as f32 + 1.0, a.y.max(b.y) as f32 + 1.0);
+            let corners: Vec<egui::Pos2> = [
+                (min_x, min_y),
+                (max_x, min_y),
+                (max_x, max_y),
+                (min_x, max_y),
+            ]
+            .into_iter()
+            .map(|(x, y)| view.grid_to_screen(egui::vec2(x, y)))
+            .collect();
+            painter.add(egui::Shape::convex_polygon(
+                corners.clone(),
+                self.theme.accent.gamma_multiply(0.15),
+                egui::Stroke::NONE,
+            ));
+            painter.add(egui::Shape::closed_line(
+                corners,
+                egui::Stroke::new(2.0, self.theme.accent),
+            ));
+        }
+
+        let minimap_clicked = if self.show_minimap {
+            self.render_minimap(ui, &painter, rect, &view)
+        } else {
+            false
+        };
+
+        if !minimap_clicked
+            && response.clicked()
             && let Some(pointer_pos) = response.interact_pointer_pos()
         {
-            let relative_pos = pointer_pos - rect.min;
-            let grid_x = (relative_pos.x / cell_size) as i32;
-            let grid_y = (relative_pos.y / cell_size) as i32;
-            self.handle_grid_click(Position::new(grid_x, grid_y));
+            self.handle_grid_click(view.screen_to_cell(pointer_pos));
         }
     }
 
-    fn render_legend(&self, ui: &mut egui::Ui) {
-        ui.group(|ui| {
-            ui.horizontal_wrapped(|ui| {
-                ui.label(egui::RichText::new("Legend").strong());
-                let legend_items = [
-                    ("Empty", CellType::Empty.color()),
-                    ("Obstacle", CellType::Obstacle.color()),
-                    ("Start", CellType::Start.color()),
-                    ("Goal", CellType::Goal.color()),
-                    ("Path", CellType::Path.color()),
-                    ("Visited", CellType::Visited.color()),
-                    ("Frontier", CellType::Frontier.color()),
-                    ("Current", CellType::Current.color()),
-                ];
+    /// Draws every comparison run in a tiled layout sharing one pan/zoom.
+    fn render_comparison(&mut self, ui: &mut egui::Ui) {
+        let Some(run_count) = self.comparison.as_ref().map(|c| c.runs.len()) else {
+            return;
+        };
+        if run_count == 0 {
+            ui.label("Select at least one algorithm to compare");
+            return;
+        }
 
-                for (name, color) in legend_items {
-                    ui.horizontal(|ui| {
-                        let (rect, _) =
-                            ui.allocate_exact_size(egui::Vec2::splat(16.0), egui::Sense::hover());
-                        ui.painter().rect_filled(rect, 2.0, color);
-                        ui.painter().rect_stroke(
-                            rect,
-                            2.0,
-                            egui::Stroke::new(1.0, self.theme.border),
-                            egui::StrokeKind::Middle,
-                        );
-                        ui.label(name);
+        let columns = (run_count as f32).sqrt().ceil() as usize;
+        let rows = run_count.div_ceil(columns);
+        let spacing = ui.spacing().item_spacing;
+        let label_height = 20.0;
+        let available = ui.available_size();
+        let tile_size = egui::vec2(
+            (available.x - spacing.x * (columns - 1) as f32) / columns as f32,
+            (available.y - spacing.y * (rows - 1) as f32) / rows as f32 - label_height,
+        )
+        .max(egui::Vec2::splat(50.0));
+
+        for row in 0..rows {
+            ui.horizontal(|ui| {
+                for index in (row * columns..(row + 1) * columns).take_while(|&i| i < run_count) {
+                    ui.vertical(|ui| {
+                        ui.set_width(tile_size.x);
+                        self.render_comparison_tile(ui, index, tile_size);
                     });
                 }
             });
+        }
+    }
+
+    fn render_comparison_tile(&mut self, ui: &mut egui::Ui, index: usize, tile_size: egui::Vec2) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        let run = &comparison.runs[index];
+        let (status, color) = match run.status {
+            RunStatus::Running => ("running", self.theme.text_primary),
+            RunStatus::Found => ("path found", self.theme.success),
+            RunStatus::NoPath => ("no path", self.theme.warning),
+        };
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(run.algorithm.name()).strong());
+            ui.label(egui::RichText::new(status).color(color));
         });
+
+        let (response, painter) = ui.allocate_painter(tile_size, egui::Sense::click_and_drag());
+        let rect = response.rect;
+        let painter = painter.with_clip_rect(rect);
+        self.handle_zoom_and_pan(ui, &response);
+
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        let run = &comparison.runs[index];
+        let view = Viewport::new(
+            rect.min + self.pan_offset,
+            self.grid.size,
+            self.grid.topology,
+        )
+        .on_floor(self.current_floor);
+        Self::paint_cells(
+            &painter,
+            &run.grid,
+            &view,
+            rect,
+            Some(comparison.start()),
+            Some(comparison.goal()),
+        );
+        painter.rect_stroke(
+            rect,
+            0.0,
+            egui::Stroke::new(1.0, self.theme.border),
+            egui::StrokeKind::Inside,
+        );
     }
 
-    fn render_statistics(&self, ui: &mut egui::Ui) {
-        egui::CollapsingHeader::new("📊 Statistics")
-            .default_open(false)
+    fn render_comparison_settings(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("⚖ Comparison")
+            .default_open(true)
             .show(ui, |ui| {
-                if let Some(state) = &self.pathfinding_state {
-                    ui.horizontal(|ui| {
-                        ui.label("Steps:");
-                        ui.label(egui::RichText::new(format!("{}", state.step_count())).strong());
-                    });
+                ui.label("Algorithms to run side by side:");
+                ui.horizontal_wrapped(|ui| {
+                    for algorithm in Algorithm::ALL
+                        .into_iter()
+                        .filter(|a| a.supports(&self.grid))
+                    {
+                        let mut enabled = self.comparison_algorithms.contains(&algorithm);
+                        if ui.checkbox(&mut enabled, algorithm.name()).changed() {
+                            if enabled {
+                                self.comparison_algorithms.push(algorithm);
+                            } else {
+                                self.comparison_algorithms.retain(|a| *a != algorithm);
+                            }
+                        }
+                    }
+                });
+            });
+    }
 
-                    ui.horizontal(|ui| {
-                        ui.label("Frontier Size:");
-                        ui.label(egui::RichText::new(format!("{}", self.frontier_len())).strong());
-                    });
+    /// Paints the cells of `grid` that fall inside `clip`.
+    fn paint_cells(
+        painter: &egui::Painter,
+        grid: &Grid,
+        view: &Viewport,
+        clip: egui::Rect,
+        start_pos: Option<Position>,
+        goal_pos: Option<Position>,
+    ) {
+        let (xs, ys) = view.visible_cells(clip, grid.width, grid.height);
+        let show_lines = view.cell_size >= 4.0;
 
-                    ui.horizontal(|ui| {
-                        ui.label("Visited Nodes:");
-                        ui.label(
-                            egui::RichText::new(format!("{}", state.closed_set_len())).strong(),
+        // Draw only the cells inside the visible rect
+        for y in ys {
+            for x in xs.clone() {
+                let pos = view.cell(x, y);
+                let cell_rect = view.cell_rect(&pos);
+
+                let mut cell_type = grid.get_cell(&pos);
+
+                // Override with start/goal positions
+                if Some(pos) == start_pos {
+                    cell_type = grid::CellType::Start;
+                } else if Some(pos) == goal_pos {
+                    cell_type = grid::CellType::Goal;
+                }
+
+                Self::fill_cell(painter, view, &pos, cell_type.color());
+                // Configuration-space inflation around obstacles
+                if grid.is_inflated(&pos) {
+                    Self::fill_cell(
+                        painter,
+                        view,
+                        &pos,
+                        grid::CellType::Obstacle.color().gamma_multiply(0.45),
+                    );
+                }
+                if let Some(connector) = grid.connector(&pos) {
+                    let center = view.cell_center(&pos);
+                    painter.circle_filled(center, view.cell_size * 0.38, connector.color());
+                    if view.cell_size >= 12.0 {
+                        painter.text(
+                            center,
+                            egui::Align2::CENTER_CENTER,
+                            connector.symbol(),
+                            egui::FontId::proportional(view.cell_size * 0.45),
+                            egui::Color32::WHITE,
                         );
-                    });
+                    }
+                }
+                if show_lines {
+                    let stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(162, 175, 155));
+                    if view.topology.is_hex() {
+                        painter.add(egui::Shape::closed_line(view.cell_polygon(&pos), stroke));
+                    } else {
+                        painter.rect_stroke(cell_rect, 0.0, stroke, egui::StrokeKind::Middle);
+                    }
+                }
+            }
+        }
+    }
 
-                    if !self.final_path.is_empty() {
-                        ui.separator();
-                        ui.horizontal(|ui| {
-                            ui.label("Path Length:");
-                            ui.label(
-                                egui::RichText::new(format!("{}", self.final_path.len()))
-                                    .strong()
-                                    .color(self.theme.success),
-                            );
-                        });
+    /// Fills one cell: a rectangle on square grids, a hexagon otherwise.
+    fn fill_cell(painter: &egui::Painter, view: &Viewport, pos: &Position, color: egui::Color32) {
+        if view.topology.is_hex() {
+            painter.add(egui::Shape::convex_polygon(
+                view.cell_polygon(pos),
+                color,
+                egui::Stroke::NONE,
+            ));
+        } else {
+            painter.rect_filled(view.cell_rect(pos), 0.0, color);
+        }
+    }
 
-                        if let Some(path_cost) =
-                            self.final_path.last().and_then(|pos| state.g_cost(pos))
-                        {
-                            ui.horizontal(|ui| {
-                                ui.label("Path Cost:");
-                                ui.label(
-                                    egui::RichText::new(format!("{}", path_cost))
-                                        .strong()
-                                        .color(self.theme.success),
-                                );
-                            });
-                        }
+    fn paint_robot(&self, painter: &egui::Painter, view: &Viewport) {
+        let Some((position, heading)) = self.robot.pose() else {
+            return;
+        };
+        if self.robot.floor() != Some(view.floor) {
+            return;
+        }
+        let center = view.grid_to_screen(position);
+        let radius = view.cell_size * 0.4;
+        // Heading is in grid space; hex layouts shear it on screen
+        let direction =
+            (view.grid_to_screen(position + egui::Vec2::angled(heading)) - center).normalized();
+        let side = egui::Vec2::new(-direction.y, direction.x);
+
+        let footprint = self.grid.robot_radius();
+        if footprint > 0.0 {
+            painter.circle(
+                center,
+                (footprint + 0.5) * view.cell_size,
+                self.theme.primary.gamma_multiply(0.15),
+                egui::Stroke::new(1.0, self.theme.primary.gamma_multiply(0.6)),
+            );
+        }
+
+        painter.circle(
+            center,
+            radius,
+            self.theme.primary,
+            egui::Stroke::new(1.5, self.theme.primary_active),
+        );
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                center + direction * radius * 0.85,
+                center - direction * radius * 0.35 + side * radius * 0.5,
+                center - direction * radius * 0.35 - side * radius * 0.5,
+            ],
+            self.theme.surface,
+            egui::Stroke::NONE,
+        ));
+    }
+
+    fn paint_exploration(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        if let Some(sim) = &self.exploration {
+            Self::paint_fog(painter, view, &sim.belief, xs, ys);
+            self.paint_route(painter, view, sim.robot, &sim.trail, &sim.plan);
+        } else if let Some(explorer) = &self.frontier_explorer {
+            Self::paint_fog(painter, view, &explorer.belief, xs.clone(), ys.clone());
+            for y in ys {
+                for x in xs.clone() {
+                    let pos = view.cell(x, y);
+                    if explorer.is_frontier(&self.grid, &pos) {
+                        painter.rect_filled(
+                            view.cell_rect(&pos).shrink(view.cell_size * 0.25),
+                            0.0,
+                            CellType::Frontier.color(),
+                        );
                     }
-                } else {
-                    ui.label("No pathfinding in progress");
                 }
-            });
+            }
+            if let Some(target) = explorer.target {
+                painter.circle_stroke(
+                    view.cell_center(&target),
+                    view.cell_size * 0.45,
+                    egui::Stroke::new(2.0, CellType::Goal.color()),
+                );
+            }
+            self.paint_route(
+                painter,
+                view,
+                explorer.robot,
+                &explorer.trail,
+                &explorer.plan,
+            );
+        }
+    }
+
+    /// Darkens cells the robot has not sensed yet.
+    fn paint_fog(
+        painter: &egui::Painter,
+        view: &Viewport,
+        belief: &BeliefMap,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        let fog = egui::Color32::from_black_alpha(140);
+        for y in ys {
+            for x in xs.clone() {
+                let pos = view.cell(x, y);
+                if !belief.is_known(&pos) {
+                    Self::fill_cell(painter, view, &pos, fog);
+                }
+            }
+        }
+    }
+
+    /// Draws the trail driven so far, the remaining plan and the robot with
+    /// its sensor range.
+    fn paint_route(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        robot: Position,
+        trail: &[Position],
+        plan: &[Position],
+    ) {
+        let trail: Vec<egui::Pos2> = trail.iter().map(|p| view.cell_center(p)).collect();
+        painter.add(egui::Shape::line(
+            trail,
+            egui::Stroke::new(2.0, self.theme.accent),
+        ));
+        let plan: Vec<egui::Pos2> = std::iter::once(robot)
+            .chain(plan.iter().copied())
+            .map(|p| view.cell_center(&p))
+            .collect();
+        painter.add(egui::Shape::dashed_line(
+            &plan,
+            egui::Stroke::new(1.5, self.theme.primary),
+            6.0,
+            4.0,
+        ));
+
+        let center = view.cell_center(&robot);
+        painter.circle_stroke(
+            center,
+            (self.sensor_radius as f32 + 0.5) * view.cell_size,
+            egui::Stroke::new(1.0, self.theme.primary.gamma_multiply(0.6)),
+        );
+        painter.circle(
+            center,
+            view.cell_size * 0.4,
+            self.theme.primary,
+            egui::Stroke::new(1.5, self.theme.primary_active),
+        );
+    }
+
+    fn render_exploration_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🌫 Exploration")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(egui::Slider::new(&mut self.sensor_radius, 1..=20).text("Sensor radius"));
+                ui.horizontal(|ui| {
+                    ui.label("Unknown cells:");
+                    ui.selectable_value(
+                        &mut self.unknown_policy,
+                        UnknownPolicy::AssumeFree,
+                        "Free",
+                    );
+                    ui.selectable_value(
+                        &mut self.unknown_policy,
+                        UnknownPolicy::AssumeBlocked,
+                        "Blocked",
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Start Simulation").clicked() {
+                        self.start_exploration();
+                    }
+                    if self.step_by_step
+                        && self.simulations_running()
+                        && ui.button("⏭ Step").clicked()
+                    {
+                        self.step_simulations();
+                    }
+                    if self.exploration.is_some() && ui.button("⏹ Stop").clicked() {
+                        self.exploration = None;
+                    }
+                });
+
+                ui.separator();
+                ui.label("Autonomous frontier exploration from the start cell:");
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Explore Map").clicked() {
+                        self.start_frontier_exploration();
+                    }
+                    if self.frontier_explorer.is_some() && ui.button("⏹ Stop").clicked() {
+                        self.frontier_explorer = None;
+                    }
+                });
+                if let Some(explorer) = &self.frontier_explorer {
+                    let (status, color) = if explorer.complete {
+                        ("Exploration complete", self.theme.success)
+                    } else {
+                        ("Exploring frontiers", self.theme.text_primary)
+                    };
+                    ui.label(egui::RichText::new(status).strong().color(color));
+                    ui.horizontal(|ui| {
+                        ui.label("Frontiers Reached:");
+                        ui.label(
+                            egui::RichText::new(format!("{}", explorer.frontiers_visited)).strong(),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Distance Travelled:");
+                        ui.label(
+                            egui::RichText::new(format!("{}", explorer.distance_travelled()))
+                                .strong(),
+                        );
+                    });
+                }
+
+                let Some(sim) = &self.exploration else {
+                    return;
+                };
+                ui.separator();
+                let (status, color) = match sim.status {
+                    SimStatus::Running => ("Exploring", self.theme.text_primary),
+                    SimStatus::Reached => ("Goal reached", self.theme.success),
+                    SimStatus::Stuck => ("No route on belief map", self.theme.warning),
+                };
+                ui.label(egui::RichText::new(status).strong().color(color));
+                ui.horizontal(|ui| {
+                    ui.label("Distance Travelled:");
+                    ui.label(egui::RichText::new(format!("{}", sim.distance_travelled())).strong());
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Omniscient Optimum:");
+                    let optimum = sim.optimum.map_or("–".to_string(), |d| d.to_string());
+                    ui.label(egui::RichText::new(optimum).strong());
+                });
+                if let Some(optimum) = sim.optimum.filter(|d| *d > 0)
+                    && sim.status == SimStatus::Reached
+                {
+                    ui.horizontal(|ui| {
+                        ui.label("Overhead:");
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{:.2}×",
+                                sim.distance_travelled() as f32 / optimum as f32
+                            ))
+                            .strong(),
+                        );
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Replans:");
+                    ui.label(egui::RichText::new(format!("{}", sim.replans)).strong());
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Map Revealed:");
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{:.1}%",
+                            100.0 * sim.belief.known_count() as f32
+                                / sim.belief.total_cells() as f32
+                        ))
+                        .strong(),
+                    );
+                });
+            });
+    }
+
+    fn render_coverage_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🧹 Coverage")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Pattern:");
+                    for pattern in [CoveragePattern::Boustrophedon, CoveragePattern::Spiral] {
+                        ui.selectable_value(&mut self.coverage_pattern, pattern, pattern.name());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Sweep").clicked() {
+                        self.start_coverage();
+                    }
+                    if self.step_by_step
+                        && self.simulations_running()
+                        && ui.button("⏭ Step").clicked()
+                    {
+                        self.step_simulations();
+                    }
+                    if let Some(coverage) = &mut self.coverage
+                        && coverage.is_running()
+                        && ui.button("⏩ Finish").clicked()
+                    {
+                        coverage.finish(&mut self.grid);
+                    }
+                });
+
+                let Some(coverage) = &self.coverage else {
+                    return;
+                };
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Progress:");
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} / {} moves",
+                            coverage.steps_taken(),
+                            coverage.total_steps()
+                        ))
+                        .strong(),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Coverage:");
+                    ui.label(
+                        egui::RichText::new(format!("{:.1}%", coverage.coverage_percent()))
+                            .strong()
+                            .color(self.theme.success),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Overlap (cells visited twice):");
+                    ui.label(
+                        egui::RichText::new(format!("{}", coverage.overlap_cells()))
+                            .strong()
+                            .color(self.theme.warning),
+                    );
+                });
+            });
+    }
+
+    /// On multi-floor maps, traces the part of the path on the shown floor
+    /// and marks where it changes floors with the floor it continues on.
+    fn paint_floor_path(&self, painter: &egui::Painter, view: &Viewport) {
+        if self.grid.floor_count() == 1 || self.final_path.len() < 2 {
+            return;
+        }
+        let stroke = egui::Stroke::new(2.5, self.theme.accent);
+        for segment in self.final_path.chunk_by(|a, b| a.floor == b.floor) {
+            if segment[0].floor == view.floor {
+                let points = segment.iter().map(|p| view.cell_center(p)).collect();
+                painter.add(egui::Shape::line(points, stroke));
+            }
+        }
+        for pair in self.final_path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if from.floor == to.floor || (from.floor != view.floor && to.floor != view.floor) {
+                continue;
+            }
+            let center = view.cell_center(&from);
+            painter.circle_stroke(center, view.cell_size * 0.5, stroke);
+            // Label with the other floor: where the path goes, or came from
+            let other = if from.floor == view.floor {
+                to.floor
+            } else {
+                from.floor
+            };
+            let arrow = if other > view.floor { "▲" } else { "▼" };
+            painter.text(
+                center - egui::vec2(0.0, view.cell_size * 0.6),
+                egui::Align2::CENTER_BOTTOM,
+                format!("{arrow}{other}"),
+                egui::FontId::proportional(12.0),
+                self.theme.accent,
+            );
+        }
+    }
+
+    fn render_floor_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🏢 Floors")
+            .default_open(false)
+            .show(ui, |ui| {
+                let floors = self.grid.floor_count() as i32;
+                // (floor, cells, last cell) for each stretch of the path
+                let segments: Vec<(i32, usize, Position)> = self
+                    .final_path
+                    .chunk_by(|a, b| a.floor == b.floor)
+                    .map(|segment| (segment[0].floor, segment.len(), segment[segment.len() - 1]))
+                    .collect();
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("View:");
+                    for floor in 0..floors {
+                        // Floors on the path are highlighted in the path colour
+                        let cells: usize = segments
+                            .iter()
+                            .filter(|(segment_floor, _, _)| *segment_floor == floor)
+                            .map(|(_, cells, _)| cells)
+                            .sum();
+                        let mut text = egui::RichText::new(format!("F{floor}"));
+                        if cells > 0 {
+                            text = text.color(CellType::Path.color()).strong();
+                        }
+                        ui.selectable_value(&mut self.current_floor, floor, text)
+                            .on_hover_text(format!("{cells} path cells"));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("➕ Add Floor").clicked() {
+                        self.add_floor();
+                    }
+                    if ui
+                        .add_enabled(floors > 1, egui::Button::new("➖ Remove Top Floor"))
+                        .clicked()
+                    {
+                        self.remove_top_floor();
+                    }
+                });
+
+                ui.separator();
+                ui.add(egui::Slider::new(&mut self.grid.stairs_cost, 1..=20).text("Stairs cost"));
+                ui.add(
+                    egui::Slider::new(&mut self.grid.elevator_cost, 1..=20).text("Elevator cost"),
+                );
+                ui.label(
+                    egui::RichText::new(
+                        "Stairs join the same cell on adjacent floors and cost that much per floor; an elevator reaches every floor with a door in its shaft for one ride. BFS and DFS count any move as one step",
+                    )
+                    .size(10.0)
+                    .italics(),
+                );
+                let (stairs, elevators) = self
+                    .grid
+                    .connectors()
+                    .fold((0, 0), |(stairs, elevators), (_, connector)| match connector {
+                        Connector::Stairs => (stairs + 1, elevators),
+                        Connector::Elevator => (stairs, elevators + 1),
+                    });
+                ui.label(format!("{stairs} stair cells, {elevators} elevator doors"));
+
+                if floors == 1 || segments.is_empty() {
+                    return;
+                }
+                ui.separator();
+                ui.label(egui::RichText::new("Path by floor").strong());
+                for (index, &(floor, cells, last)) in segments.iter().enumerate() {
+                    if ui
+                        .selectable_label(
+                            self.current_floor == floor,
+                            format!("Floor {floor}: {cells} cells"),
+                        )
+                        .clicked()
+                    {
+                        self.current_floor = floor;
+                    }
+                    if index + 1 < segments.len() {
+                        let via = self.grid.connector(&last).map_or("?", |c| c.name());
+                        ui.label(
+                            egui::RichText::new(format!("  ↳ {via} at ({}, {})", last.x, last.y))
+                                .size(10.0),
+                        );
+                    }
+                }
+            });
+    }
+
+    fn paint_waypoints(&self, painter: &egui::Painter, view: &Viewport) {
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            if waypoint.floor != view.floor {
+                continue;
+            }
+            // Number waypoints by visiting order once a tour is planned
+            let label = self
+                .tour
+                .as_ref()
+                .and_then(|tour| tour.order.iter().position(|&i| i == index))
+                .unwrap_or(index)
+                + 1;
+            let center = view.cell_center(waypoint);
+            let radius = view.cell_size * 0.42;
+            painter.circle(
+                center,
+                radius,
+                self.theme.accent,
+                egui::Stroke::new(1.0, self.theme.surface),
+            );
+            if view.cell_size >= 10.0 {
+                painter.text(
+                    center,
+                    egui::Align2::CENTER_CENTER,
+                    label.to_string(),
+                    egui::FontId::proportional(radius * 1.1),
+                    self.theme.surface,
+                );
+            }
+        }
+    }
+
+    fn render_waypoint_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📍 Waypoints")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(format!(
+                    "{} waypoints (use the Waypoint tool to add or remove)",
+                    self.waypoints.len()
+                ));
+                ui.checkbox(&mut self.waypoints_ordered, "Visit in placement order");
+                if !self.waypoints_ordered && self.waypoints.len() > routing::EXACT_LIMIT {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "More than {} waypoints: using nearest-neighbour + 2-opt",
+                            routing::EXACT_LIMIT
+                        ))
+                        .size(10.0)
+                        .italics(),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.waypoints.is_empty(), egui::Button::new("▶ Plan Tour"))
+                        .clicked()
+                    {
+                        self.plan_tour();
+                    }
+                    if ui.button("🗑 Clear Waypoints").clicked() {
+                        self.waypoints.clear();
+                        self.clear_visualization();
+                    }
+                });
+                if let Some(error) = &self.tour_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+            });
+    }
+
+    fn paint_agents(&self, painter: &egui::Painter, view: &Viewport) {
+        let radius = view.cell_size * 0.38;
+        let positions = self.mapf.as_ref().map(MapfRun::positions);
+
+        for (i, agent) in self.agents.iter().enumerate() {
+            let color = AGENT_COLORS[i % AGENT_COLORS.len()];
+            if let Some(run) = &self.mapf
+                && let Some(path) = run.solution.paths.get(i)
+            {
+                let points: Vec<egui::Pos2> = path.iter().map(|p| view.cell_center(p)).collect();
+                painter.add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(1.5, color.gamma_multiply(0.5)),
+                ));
+            }
+            painter.rect_stroke(
+                view.cell_rect(&agent.start).shrink(view.cell_size * 0.15),
+                2.0,
+                egui::Stroke::new(1.5, color),
+                egui::StrokeKind::Middle,
+            );
+            painter.circle_stroke(
+                view.cell_center(&agent.goal),
+                radius,
+                egui::Stroke::new(2.0, color),
+            );
+
+            let current = positions
+                .as_ref()
+                .and_then(|p| p.get(i).copied())
+                .unwrap_or(agent.start);
+            painter.circle(
+                view.cell_center(&current),
+                radius,
+                color,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
+        }
+
+        if let Some(start) = self.pending_agent_start {
+            let color = AGENT_COLORS[self.agents.len() % AGENT_COLORS.len()];
+            painter.circle_stroke(
+                view.cell_center(&start),
+                radius,
+                egui::Stroke::new(2.0, color.gamma_multiply(0.6)),
+            );
+        }
+
+        // Highlight conflicts happening at the current time step
+        if let Some(run) = &self.mapf {
+            for conflict in &run.solution.conflicts {
+                let current = conflict.time() == run.time;
+                for cell in conflict.cells() {
+                    let rect = view.cell_rect(&cell);
+                    painter.rect_stroke(
+                        rect,
+                        0.0,
+                        egui::Stroke::new(
+                            if current { 3.0 } else { 1.0 },
+                            egui::Color32::RED.gamma_multiply(if current { 1.0 } else { 0.5 }),
+                        ),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Time at which moving obstacles are drawn: the robot's progress once it
+    /// has a path to drive, otherwise the preview slider.
+    fn obstacle_time(&self) -> f32 {
+        if self.robot.has_path() && (self.robot.playing || self.robot.progress() > 0.0) {
+            self.robot.progress()
+        } else {
+            self.obstacle_preview_time as f32
+        }
+    }
+
+    fn paint_moving_obstacles(&self, painter: &egui::Painter, view: &Viewport) {
+        let color = egui::Color32::from_rgb(230, 81, 0);
+        let time = self.obstacle_time();
+
+        for obstacle in &self.dynamic_obstacles {
+            let points: Vec<egui::Pos2> = obstacle
+                .route()
+                .iter()
+                .map(|p| view.cell_center(p))
+                .collect();
+            painter.add(egui::Shape::dashed_line(
+                &points,
+                egui::Stroke::new(1.5, color.gamma_multiply(0.5)),
+                6.0,
+                4.0,
+            ));
+
+            let (x, y) = obstacle.interpolated_at(time);
+            let center = view.grid_to_screen(egui::vec2(x + 0.5, y + 0.5));
+            painter.rect(
+                egui::Rect::from_center_size(center, egui::Vec2::splat(view.cell_size * 0.8)),
+                view.cell_size * 0.15,
+                color,
+                egui::Stroke::new(1.0, egui::Color32::BLACK),
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        if !self.pending_obstacle_route.is_empty() {
+            let points: Vec<egui::Pos2> = self
+                .pending_obstacle_route
+                .iter()
+                .map(|p| view.cell_center(p))
+                .collect();
+            for point in &points {
+                painter.circle_stroke(*point, view.cell_size * 0.3, egui::Stroke::new(2.0, color));
+            }
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+        }
+    }
+
+    fn render_moving_obstacle_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🚧 Moving Obstacles")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    "Use the Moving Obstacle tool to click route waypoints, then finish the route",
+                );
+                ui.label(format!(
+                    "{} obstacles, {} pending waypoints",
+                    self.dynamic_obstacles.len(),
+                    self.pending_obstacle_route.len()
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !self.pending_obstacle_route.is_empty(),
+                            egui::Button::new("✔ Finish Route"),
+                        )
+                        .clicked()
+                    {
+                        let route = std::mem::take(&mut self.pending_obstacle_route);
+                        self.dynamic_obstacles
+                            .extend(DynamicObstacle::from_waypoints(&route));
+                    }
+                    if ui.button("🗑 Clear Moving Obstacles").clicked() {
+                        self.dynamic_obstacles.clear();
+                        self.pending_obstacle_route.clear();
+                        self.obstacle_preview_time = 0;
+                    }
+                });
+
+                if self.dynamic_obstacles.is_empty() {
+                    return;
+                }
+                let cells = self.grid.width * self.grid.height;
+                let period = dynamic_obstacle::combined_period(&self.dynamic_obstacles, cells);
+                ui.add(
+                    egui::Slider::new(
+                        &mut self.obstacle_preview_time,
+                        0..=period.saturating_sub(1),
+                    )
+                    .text("Preview time"),
+                );
+                if period < cells {
+                    ui.label(format!("Routes repeat every {period} steps"));
+                } else {
+                    ui.label(format!("Routes take over {cells} steps to repeat"));
+                }
+                if self.current_algorithm != Algorithm::SpaceTimeAStar {
+                    ui.label(
+                        egui::RichText::new("Only Space-time A* plans around moving obstacles")
+                            .size(10.0)
+                            .italics(),
+                    );
+                } else if self.robot.has_path() {
+                    ui.label(
+                        egui::RichText::new("Obstacles follow the robot's clock while it drives")
+                            .size(10.0)
+                            .italics(),
+                    );
+                }
+            });
+    }
+
+    fn render_clearance_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📏 Clearance")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.show_distance_field, "Show distance field");
+                ui.add(
+                    egui::Slider::new(&mut self.clearance_weight, 0.0..=5.0)
+                        .step_by(0.25)
+                        .text("Clearance weight"),
+                );
+                ui.label(
+                    egui::RichText::new(format!(
+                        "A* pays up to weight × {} extra per cell closer than {} cells to a wall",
+                        distance_field::CLEARANCE_RANGE,
+                        distance_field::CLEARANCE_RANGE
+                    ))
+                    .size(10.0)
+                    .italics(),
+                );
+
+                if self.final_path.is_empty() {
+                    return;
+                }
+                let clearances: Vec<f32> = self
+                    .final_path
+                    .iter()
+                    .map(|p| {
+                        self.distance_cache.get(&self.grid, p.floor).0[p.y as usize][p.x as usize]
+                    })
+                    .collect();
+                let min = clearances.iter().copied().fold(f32::INFINITY, f32::min);
+                let mean = clearances.iter().sum::<f32>() / clearances.len() as f32;
+                if min.is_finite() {
+                    ui.label(format!(
+                        "Path clearance: min {min:.2}, mean {mean:.2} cells"
+                    ));
+                }
+            });
+    }
+
+    /// Small arrows in each path cell pointing along the robot's heading.
+    fn paint_heading_arrows(&self, painter: &egui::Painter, view: &Viewport) {
+        if !self.show_heading_arrows || view.cell_size < 8.0 {
+            return;
+        }
+        let headings = heading::path_headings(&self.final_path);
+        let mut cells = self.final_path.iter().zip(&self.final_path[1..]);
+        let mut last = None;
+        for heading in headings {
+            // Waits repeat a cell without a move; skip to the next real move
+            let Some((from, _)) = cells.find(|(from, to)| from != to) else {
+                break;
+            };
+            self.paint_heading_arrow(painter, view, from, heading);
+            last = Some(heading);
+        }
+        if let (Some(goal), Some(heading)) = (self.final_path.last(), last) {
+            self.paint_heading_arrow(painter, view, goal, heading);
+        }
+    }
+
+    fn paint_heading_arrow(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        cell: &Position,
+        heading: Heading,
+    ) {
+        let (dx, dy) = heading.offset();
+        let direction = egui::vec2(dx as f32, dy as f32).normalized();
+        let side = egui::vec2(-direction.y, direction.x);
+        let center = view.cell_center(cell);
+        let size = view.cell_size * 0.3;
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                center + direction * size,
+                center - direction * size * 0.6 + side * size * 0.6,
+                center - direction * size * 0.6 - side * size * 0.6,
+            ],
+            self.theme.primary.gamma_multiply(0.8),
+            egui::Stroke::NONE,
+        ));
+    }
+
+    fn paint_lattice_plan(&self, painter: &egui::Painter, view: &Viewport) {
+        let Some(plan) = &self.lattice_plan else {
+            return;
+        };
+        let points: Vec<egui::Pos2> = plan
+            .trajectory
+            .iter()
+            .map(|p| view.grid_to_screen(*p))
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new((view.cell_size * 0.15).clamp(1.5, 4.0), self.theme.accent),
+        ));
+        if view.cell_size >= 8.0 {
+            for (cell, heading) in &plan.poses {
+                self.paint_heading_arrow(painter, view, cell, *heading);
+            }
+        }
+    }
+
+    fn paint_hybrid_plan(&self, painter: &egui::Painter, view: &Viewport) {
+        let Some(plan) = &self.hybrid_plan else {
+            return;
+        };
+        if self.show_hybrid_tree {
+            let stroke = egui::Stroke::new(1.0, self.theme.border.gamma_multiply(0.6));
+            for motion in &plan.tree {
+                let points = motion.iter().map(|p| view.grid_to_screen(*p)).collect();
+                painter.add(egui::Shape::line(points, stroke));
+            }
+        }
+
+        let width = (view.cell_size * 0.12).clamp(1.5, 4.0);
+        for (i, pair) in plan.trajectory.windows(2).enumerate() {
+            let color = if i >= plan.analytic_start {
+                self.theme.success
+            } else if pair[1].1 {
+                self.theme.warning
+            } else {
+                self.theme.accent
+            };
+            painter.line_segment(
+                [
+                    view.grid_to_screen(pair[0].0.point()),
+                    view.grid_to_screen(pair[1].0.point()),
+                ],
+                egui::Stroke::new(width, color),
+            );
+        }
+
+        // Vehicle outlines along the way, about every two cells
+        let mut travelled = f32::INFINITY;
+        let mut previous = None;
+        for (pose, _) in &plan.trajectory {
+            if let Some(previous) = previous {
+                travelled += (pose.point() - previous).length();
+            }
+            previous = Some(pose.point());
+            if travelled >= 2.0 {
+                self.paint_vehicle(painter, view, pose, 0.35);
+                travelled = 0.0;
+            }
+        }
+        if let Some((pose, _)) = plan.trajectory.last() {
+            self.paint_vehicle(painter, view, pose, 1.0);
+        }
+    }
+
+    /// Outline of a car-like vehicle centred on the rear axle's pose.
+    fn paint_vehicle(&self, painter: &egui::Painter, view: &Viewport, pose: &Pose, alpha: f32) {
+        let forward = egui::Vec2::angled(pose.theta);
+        let side = egui::vec2(-forward.y, forward.x);
+        let center = pose.point() + forward * hybrid_astar::WHEELBASE * 0.5;
+        let (half_length, half_width) = ((hybrid_astar::WHEELBASE + 0.6) * 0.5, 0.35);
+        let corners = [(1.0, 1.0), (1.0, -1.0), (-1.0, -1.0), (-1.0, 1.0)].map(|(f, s)| {
+            view.grid_to_screen(center + forward * f * half_length + side * s * half_width)
+        });
+        painter.add(egui::Shape::closed_line(
+            corners.to_vec(),
+            egui::Stroke::new(1.5, self.theme.primary_active.gamma_multiply(alpha)),
+        ));
+        let nose = view.grid_to_screen(center + forward * half_length);
+        painter.circle_filled(nose, 2.5, self.theme.primary_active.gamma_multiply(alpha));
+    }
+
+    /// Small arrow centred on `center` pointing along `direction`.
+    fn paint_vector_arrow(
+        painter: &egui::Painter,
+        center: egui::Pos2,
+        direction: egui::Vec2,
+        size: f32,
+        color: egui::Color32,
+    ) {
+        let direction = direction.normalized();
+        let side = egui::vec2(-direction.y, direction.x);
+        let tip = center + direction * size;
+        let tail = center - direction * size;
+        let head = tip - direction * size * 0.7;
+        painter.line_segment([tail, head], egui::Stroke::new(1.2, color));
+        painter.add(egui::Shape::convex_polygon(
+            vec![tip, head + side * size * 0.4, head - side * size * 0.4],
+            color,
+            egui::Stroke::NONE,
+        ));
+    }
+
+    fn paint_potential(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        if self.show_potential_field
+            && view.cell_size >= 8.0
+            && let Some(goal) = self.goal_pos
+        {
+            let goal = smoothing::cell_center(&goal);
+            for y in ys {
+                for x in xs.clone() {
+                    let cell = view.cell(x, y);
+                    if !self.grid.is_walkable(&cell) {
+                        continue;
+                    }
+                    let force = potential_field::force(
+                        &self.grid,
+                        &self.potential_config,
+                        goal,
+                        smoothing::cell_center(&cell),
+                    );
+                    if force.length() < 1e-4 {
+                        continue;
+                    }
+                    // Log scale so both gentle and steep regions stay readable
+                    let t = (force.length().ln_1p() / 4.0).min(1.0);
+                    Self::paint_vector_arrow(
+                        painter,
+                        view.cell_center(&cell),
+                        force,
+                        view.cell_size * 0.35,
+                        Theme::gradient(t).gamma_multiply(0.8),
+                    );
+                }
+            }
+        }
+
+        let Some(run) = &self.potential else {
+            return;
+        };
+        let points = run
+            .trajectory
+            .iter()
+            .map(|p| view.grid_to_screen(*p))
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new((view.cell_size * 0.12).clamp(1.5, 3.0), self.theme.accent),
+        ));
+        let robot = view.grid_to_screen(run.position());
+        painter.circle_filled(robot, (view.cell_size * 0.25).max(3.0), self.theme.accent);
+        if let DescentStatus::LocalMinimum(point) = run.status {
+            let center = view.grid_to_screen(point);
+            let radius = (view.cell_size * 0.8).max(8.0);
+            painter.circle_filled(center, radius, self.theme.warning.gamma_multiply(0.3));
+            painter.circle_stroke(center, radius, egui::Stroke::new(2.0, self.theme.warning));
+            painter.text(
+                center + egui::vec2(0.0, -radius - 2.0),
+                egui::Align2::CENTER_BOTTOM,
+                "Local minimum",
+                egui::FontId::proportional(12.0),
+                self.theme.warning,
+            );
+        }
+    }
+
+    fn paint_landmarks(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        if let (Some(landmarks), Some(index)) = (&self.landmarks, self.shown_landmark_table) {
+            let max_distance = landmarks.max_distance(index).max(1) as f32;
+            for y in ys {
+                for x in xs.clone() {
+                    let cell = view.cell(x, y);
+                    if let Some(d) = landmarks.distance(index, &cell) {
+                        Self::fill_cell(
+                            painter,
+                            view,
+                            &cell,
+                            Theme::gradient(d as f32 / max_distance).gamma_multiply(0.5),
+                        );
+                    }
+                }
+            }
+        }
+
+        let radius = (view.cell_size * 0.45).max(5.0);
+        for (i, landmark) in self.landmark_positions.iter().enumerate() {
+            let center = view.cell_center(landmark);
+            // Five-pointed star
+            let points: Vec<egui::Pos2> = (0..10)
+                .map(|k| {
+                    let r = if k % 2 == 0 { radius } else { radius * 0.45 };
+                    let angle =
+                        -std::f32::consts::FRAC_PI_2 + k as f32 * std::f32::consts::PI / 5.0;
+                    center + egui::vec2(angle.cos(), angle.sin()) * r
+                })
+                .collect();
+            painter.add(egui::Shape::closed_line(
+                points.clone(),
+                egui::Stroke::new(1.5, egui::Color32::WHITE),
+            ));
+            for k in 0..10 {
+                painter.add(egui::Shape::convex_polygon(
+                    vec![center, points[k], points[(k + 1) % 10]],
+                    self.theme.warning,
+                    egui::Stroke::NONE,
+                ));
+            }
+            if view.cell_size >= 16.0 {
+                painter.text(
+                    center,
+                    egui::Align2::CENTER_CENTER,
+                    format!("{}", i + 1),
+                    egui::FontId::proportional((view.cell_size * 0.3).min(11.0)),
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+    }
+
+    fn render_landmark_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("⭐ ALT Landmarks")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Place landmarks with the Landmark tool, or pick them automatically.",
+                    )
+                    .size(10.0)
+                    .italics(),
+                );
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut self.landmark_count, 1..=16).text("Count"));
+                    if ui.button("🎯 Farthest-point").clicked() {
+                        self.select_farthest_landmarks();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !self.landmark_positions.is_empty(),
+                            egui::Button::new("🔄 Recompute Tables"),
+                        )
+                        .clicked()
+                    {
+                        self.compute_landmarks();
+                    }
+                    if ui.button("🗑 Clear Landmarks").clicked() {
+                        self.landmark_positions.clear();
+                        self.compute_landmarks();
+                    }
+                });
+                ui.checkbox(&mut self.use_alt, "Use ALT heuristic for A*");
+
+                let Some(landmarks) = &self.landmarks else {
+                    return;
+                };
+                if landmarks.is_stale(&self.grid) {
+                    ui.label(
+                        egui::RichText::new(
+                            "Obstacles changed since the tables were computed; recompute them",
+                        )
+                        .color(self.theme.warning),
+                    );
+                }
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Show table:");
+                    ui.selectable_value(&mut self.shown_landmark_table, None, "None");
+                    for i in 0..landmarks.positions.len() {
+                        ui.selectable_value(
+                            &mut self.shown_landmark_table,
+                            Some(i),
+                            format!("{}", i + 1),
+                        );
+                    }
+                });
+
+                ui.separator();
+                if ui.button("📊 Compare with Manhattan").clicked() {
+                    self.compare_alt();
+                }
+                if let Some((alt, manhattan)) = self.alt_comparison {
+                    egui::Grid::new("alt_comparison")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("A* with ALT");
+                            ui.label(format!("{alt} nodes expanded"));
+                            ui.end_row();
+                            ui.label("A* with Manhattan");
+                            ui.label(format!("{manhattan} nodes expanded"));
+                            ui.end_row();
+                        });
+                    let saved = manhattan.saturating_sub(alt);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{saved} fewer expansions ({:.0}%)",
+                            100.0 * saved as f32 / manhattan.max(1) as f32
+                        ))
+                        .strong()
+                        .color(self.theme.success),
+                    );
+                }
+            });
+    }
+
+    fn paint_visibility(&self, painter: &egui::Painter, view: &Viewport) {
+        let Some(graph) = &self.visibility else {
+            return;
+        };
+        if self.show_visibility_polygons {
+            let stroke = egui::Stroke::new(2.0, self.theme.warning);
+            for polygon in &graph.polygons {
+                let points = polygon.iter().map(|p| view.grid_to_screen(*p)).collect();
+                painter.add(egui::Shape::closed_line(points, stroke));
+            }
+        }
+        if self.show_visibility_edges {
+            let stroke = egui::Stroke::new(1.0, self.theme.accent.gamma_multiply(0.35));
+            for &(a, b) in &graph.edges {
+                painter.line_segment(
+                    [
+                        view.grid_to_screen(graph.nodes[a]),
+                        view.grid_to_screen(graph.nodes[b]),
+                    ],
+                    stroke,
+                );
+            }
+        }
+        for node in &graph.nodes[..graph.corner_count()] {
+            painter.circle_filled(view.grid_to_screen(*node), 3.0, self.theme.accent);
+        }
+        if let Some(path) = &graph.path {
+            let points: Vec<egui::Pos2> = path.iter().map(|p| view.grid_to_screen(*p)).collect();
+            painter.add(egui::Shape::line(
+                points.clone(),
+                egui::Stroke::new(3.0, self.theme.success),
+            ));
+            for point in points {
+                painter.circle(
+                    point,
+                    4.0,
+                    self.theme.success,
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+        }
+    }
+
+    fn render_visibility_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🔺 Visibility Graph")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Exact any-angle shortest path around the obstacle outlines",
+                    )
+                    .size(10.0)
+                    .italics(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Build & Solve").clicked() {
+                        self.plan_visibility();
+                    }
+                    if self.visibility.is_some() && ui.button("🗑 Clear").clicked() {
+                        self.visibility = None;
+                        self.visibility_astar_length = None;
+                    }
+                });
+                ui.checkbox(&mut self.show_visibility_polygons, "Show obstacle polygons");
+                ui.checkbox(&mut self.show_visibility_edges, "Show visibility edges");
+                if let Some(error) = &self.visibility_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+
+                let Some(graph) = &self.visibility else {
+                    return;
+                };
+                ui.separator();
+                ui.label(format!(
+                    "{} polygons, {} convex corners, {} edges",
+                    graph.polygons.len(),
+                    graph.corner_count(),
+                    graph.edges.len()
+                ));
+                let Some(exact) = graph.path_length() else {
+                    return;
+                };
+                egui::Grid::new("visibility_lengths")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Visibility graph");
+                        ui.label(format!("{exact:.2} cells"));
+                        ui.end_row();
+                        if let Some(astar) = self.visibility_astar_length {
+                            ui.label("Grid A*");
+                            ui.label(format!("{astar} cells"));
+                            ui.end_row();
+                        }
+                    });
+                if let Some(astar) = self.visibility_astar_length {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Grid A* is {:.1}% longer than the exact path",
+                            100.0 * (astar as f32 / exact.max(f32::EPSILON) - 1.0)
+                        ))
+                        .strong()
+                        .color(self.theme.success),
+                    );
+                }
+            });
+    }
+
+    fn paint_hpa(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        let Some(hierarchy) = &self.hierarchy else {
+            return;
+        };
+        let visible = |pos: &Position| xs.contains(&pos.x) && ys.contains(&pos.y);
+        if self.show_hpa_overlay {
+            let line = egui::Stroke::new(1.5, self.theme.primary_active.gamma_multiply(0.7));
+            for cluster in hierarchy.clusters() {
+                let (min, max) = hierarchy.bounds(cluster);
+                if max.x < xs.start || min.x >= xs.end || max.y < ys.start || min.y >= ys.end {
+                    continue;
+                }
+                let rect = view.cell_rect(&min).union(view.cell_rect(&max));
+                painter.rect_stroke(rect, 0.0, line, egui::StrokeKind::Middle);
+            }
+            if self.show_hpa_edges {
+                let stroke = egui::Stroke::new(1.0, self.theme.accent.gamma_multiply(0.4));
+                for (from, to, _) in hierarchy.intra_edges() {
+                    if visible(&from) || visible(&to) {
+                        painter
+                            .line_segment([view.cell_center(&from), view.cell_center(&to)], stroke);
+                    }
+                }
+            }
+            let size = (view.cell_size * 0.22).max(2.0);
+            for (p, q) in hierarchy.transitions() {
+                if !visible(p) && !visible(q) {
+                    continue;
+                }
+                painter.line_segment(
+                    [view.cell_center(p), view.cell_center(q)],
+                    egui::Stroke::new(2.0, self.theme.accent),
+                );
+                for cell in [p, q] {
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(
+                            view.cell_center(cell),
+                            egui::Vec2::splat(size * 2.0),
+                        ),
+                        1.0,
+                        self.theme.accent,
+                    );
+                }
+            }
+        }
+
+        if let Some(result) = &self.hpa_result {
+            let points: Vec<egui::Pos2> = result
+                .abstract_path
+                .iter()
+                .map(|p| view.cell_center(p))
+                .collect();
+            let stroke = egui::Stroke::new(2.5, self.theme.warning);
+            painter.extend(egui::Shape::dashed_line(&points, stroke, 8.0, 4.0));
+            for point in points {
+                painter.circle(
+                    point,
+                    4.0,
+                    self.theme.warning,
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+        }
+    }
+
+    fn render_hpa_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🗺 Hierarchical (HPA*)")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(egui::Slider::new(&mut self.hpa_cluster_size, 4..=50).text("Cluster size"));
+                ui.horizontal(|ui| {
+                    if ui.button("🔨 Build Clusters").clicked() {
+                        self.build_hierarchy();
+                    }
+                    if ui.button("▶ Find Path").clicked() {
+                        self.plan_hpa();
+                    }
+                    if self.hierarchy.is_some() && ui.button("🗑 Discard").clicked() {
+                        self.hierarchy = None;
+                        self.hpa_result = None;
+                    }
+                });
+                ui.checkbox(&mut self.show_hpa_overlay, "Show clusters and entrances");
+                ui.add_enabled(
+                    self.show_hpa_overlay,
+                    egui::Checkbox::new(
+                        &mut self.show_hpa_edges,
+                        "Show cached intra-cluster edges",
+                    ),
+                );
+                if let Some(error) = &self.hpa_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+
+                let Some(hierarchy) = &self.hierarchy else {
+                    ui.label(
+                        egui::RichText::new(
+                            "Walls edited after building update only the affected clusters",
+                        )
+                        .size(10.0)
+                        .italics(),
+                    );
+                    return;
+                };
+                ui.separator();
+                ui.label(format!(
+                    "{} clusters, {} abstract nodes, {} entrances, {} cached edges",
+                    hierarchy.clusters().count(),
+                    hierarchy.node_count(),
+                    hierarchy.transitions().count(),
+                    hierarchy.intra_edges().count()
+                ));
+                if let Some(result) = &self.hpa_result {
+                    ui.label(format!(
+                        "Path {} cells via {} abstract nodes",
+                        result.path.len() - 1,
+                        result.abstract_path.len()
+                    ));
+                    egui::Grid::new("hpa_expansions")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Abstract search");
+                            ui.label(format!("{} nodes", result.abstract_expanded));
+                            ui.end_row();
+                            ui.label("Refinement");
+                            ui.label(format!("{} cells", result.refine_expanded));
+                            ui.end_row();
+                            if let Some(flat) = self.hpa_flat_expanded {
+                                ui.label("Flat A*");
+                                ui.label(format!("{flat} cells"));
+                                ui.end_row();
+                            }
+                        });
+                }
+            });
+    }
+
+    fn paint_flow_field(
+        &self,
+        painter: &egui::Painter,
+        view: &Viewport,
+        xs: std::ops::Range<i32>,
+        ys: std::ops::Range<i32>,
+    ) {
+        if let Some(field) = &self.flow_field {
+            let max_distance = field.max_distance().max(1) as f32;
+            for y in ys {
+                for x in xs.clone() {
+                    let cell = view.cell(x, y);
+                    if self.show_flow_distance
+                        && let Some(d) = field.distance(&cell)
+                    {
+                        painter.rect_filled(
+                            view.cell_rect(&cell),
+                            0.0,
+                            Theme::gradient(d as f32 / max_distance).gamma_multiply(0.5),
+                        );
+                    }
+                    if view.cell_size >= 8.0
+                        && let Some(next) = field.next_cell(&cell)
+                    {
+                        Self::paint_vector_arrow(
+                            painter,
+                            view.cell_center(&cell),
+                            view.cell_center(&next) - view.cell_center(&cell),
+                            view.cell_size * 0.3,
+                            self.theme.primary.gamma_multiply(0.7),
+                        );
+                    }
+                }
+            }
+        }
+
+        let radius = (view.cell_size * 0.18).max(2.5);
+        for agent in &self.flow_agents {
+            let center = view.grid_to_screen(agent.position);
+            let color = if agent.arrived {
+                self.theme.success.gamma_multiply(0.5)
+            } else if self
+                .flow_field
+                .as_ref()
+                .is_some_and(|field| agent.is_stranded(field))
+            {
+                self.theme.warning
+            } else {
+                self.theme.accent
+            };
+            painter.circle(
+                center,
+                radius,
+                color,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
+        }
+    }
+
+    fn render_flow_field_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🌊 Flow Field")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "One wavefront from the goal guides every agent. Use the Flow Agent tool to drop agents.",
+                    )
+                    .size(10.0)
+                    .italics(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("🔄 Compute Field").clicked() {
+                        self.flow_field = None;
+                        self.ensure_flow_field();
+                    }
+                    ui.checkbox(&mut self.show_flow_distance, "Show distances");
+                });
+                ui.add(
+                    egui::Slider::new(&mut self.flow_speed, 0.5..=10.0).text("Speed (cells/s)"),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("➕ Scatter 25").clicked() {
+                        self.scatter_flow_agents(25);
+                    }
+                    let play_label = if self.flow_playing {
+                        "⏸ Pause"
+                    } else {
+                        "▶ Play"
+                    };
+                    if ui
+                        .add_enabled(
+                            !self.flow_agents.is_empty(),
+                            egui::Button::new(play_label),
+                        )
+                        .clicked()
+                    {
+                        self.flow_playing = !self.flow_playing && self.ensure_flow_field();
+                    }
+                    if ui.button("🗑 Clear Agents").clicked() {
+                        self.flow_agents.clear();
+                        self.flow_playing = false;
+                    }
+                });
+                if self.goal_pos.is_none() {
+                    ui.label(
+                        egui::RichText::new("Place a goal to compute the field")
+                            .color(self.theme.warning),
+                    );
+                }
+
+                let Some(field) = &self.flow_field else {
+                    return;
+                };
+                ui.separator();
+                ui.label(format!(
+                    "{} cells reach the goal, farthest {:.1} cells away",
+                    field.reachable_count(),
+                    field.max_distance() as f32 / heading::STRAIGHT_COST as f32
+                ));
+                let arrived = self.flow_agents.iter().filter(|a| a.arrived).count();
+                let stranded = self
+                    .flow_agents
+                    .iter()
+                    .filter(|a| a.is_stranded(field))
+                    .count();
+                ui.label(format!(
+                    "Agents: {} total, {} arrived, {} stranded",
+                    self.flow_agents.len(),
+                    arrived,
+                    stranded
+                ));
+            });
+    }
+
+    fn render_potential_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🧲 Potential Field")
+            .default_open(false)
+            .show(ui, |ui| {
+                let config = &mut self.potential_config;
+                ui.add(
+                    egui::Slider::new(&mut config.attractive_gain, 0.1..=5.0)
+                        .text("Attractive gain"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut config.repulsive_gain, 0.0..=20.0)
+                        .text("Repulsive gain"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut config.influence_radius, 1.0..=6.0)
+                        .text("Influence radius"),
+                );
+                ui.add(egui::Slider::new(&mut config.step_size, 0.05..=0.5).text("Step size"));
+                ui.checkbox(&mut self.show_potential_field, "Show force field");
+
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Descend").clicked() {
+                        self.start_potential_descent();
+                    }
+                    if self.step_by_step
+                        && self.simulations_running()
+                        && ui.button("⏭ Step").clicked()
+                    {
+                        self.step_simulations();
+                    }
+                    if let Some(run) = &mut self.potential
+                        && run.is_running()
+                        && ui.button("⏩ Finish").clicked()
+                    {
+                        run.finish(&self.grid);
+                    }
+                });
+
+                let Some(run) = &self.potential else {
+                    return;
+                };
+                ui.separator();
+                ui.label(format!(
+                    "{} steps, {:.1} cells travelled",
+                    run.trajectory.len() - 1,
+                    run.length()
+                ));
+                let (text, color) = match run.status {
+                    DescentStatus::Running => ("Descending…", self.theme.text_primary),
+                    DescentStatus::Reached => ("Reached the goal", self.theme.success),
+                    DescentStatus::LocalMinimum(_) => {
+                        ("Trapped in a local minimum", self.theme.warning)
+                    }
+                    DescentStatus::Collided => ("Stepped into an obstacle", self.theme.warning),
+                    DescentStatus::StepLimit => ("Step limit reached", self.theme.warning),
+                };
+                ui.label(egui::RichText::new(text).strong().color(color));
+            });
+    }
+
+    fn paint_sampling(&self, painter: &egui::Painter, view: &Viewport) {
+        let edge = egui::Stroke::new(1.0, self.theme.primary.gamma_multiply(0.5));
+        let node_radius = (view.cell_size * 0.08).clamp(1.0, 3.0);
+        let path_stroke =
+            egui::Stroke::new((view.cell_size * 0.15).clamp(1.5, 4.0), self.theme.success);
+
+        if let Some(roadmap) = &self.roadmap {
+            for (i, neighbors) in roadmap.neighbors.iter().enumerate() {
+                for &j in neighbors.iter().filter(|&&j| j > i) {
+                    painter.line_segment(
+                        [
+                            view.grid_to_screen(roadmap.nodes[i]),
+                            view.grid_to_screen(roadmap.nodes[j]),
+                        ],
+                        edge,
+                    );
+                }
+            }
+            for node in &roadmap.nodes {
+                painter.circle_filled(view.grid_to_screen(*node), node_radius, self.theme.primary);
+            }
+            if let Some(path) = &self.roadmap_path {
+                let points = path.iter().map(|p| view.grid_to_screen(*p)).collect();
+                painter.add(egui::Shape::line(points, path_stroke));
+            }
+        }
+
+        if let Some(run) = &self.tree_run {
+            for (node, parent) in run.nodes.iter().zip(&run.parents) {
+                if let Some(parent) = parent {
+                    painter.line_segment(
+                        [
+                            view.grid_to_screen(run.nodes[*parent]),
+                            view.grid_to_screen(*node),
+                        ],
+                        edge,
+                    );
+                }
+            }
+            for node in &run.nodes {
+                painter.circle_filled(view.grid_to_screen(*node), node_radius, self.theme.primary);
+            }
+            if run.is_running()
+                && let Some(sample) = run.last_sample
+            {
+                painter.circle_stroke(
+                    view.grid_to_screen(sample),
+                    node_radius * 2.0,
+                    egui::Stroke::new(1.5, self.theme.accent),
+                );
+            }
+            let points = run.path.iter().map(|p| view.grid_to_screen(*p)).collect();
+            painter.add(egui::Shape::line(points, path_stroke));
+        }
+    }
+
+    fn render_sampling_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🎲 Sampling Planners")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Planner:");
+                    for planner in SamplingPlanner::ALL {
+                        ui.selectable_value(&mut self.sampling_planner, planner, planner.name());
+                    }
+                });
+                let config = &mut self.sampling_config;
+                ui.horizontal(|ui| {
+                    ui.label("Seed:");
+                    ui.add(egui::DragValue::new(&mut config.seed));
+                });
+                if self.sampling_planner == SamplingPlanner::Prm {
+                    ui.add(
+                        egui::Slider::new(&mut config.roadmap_samples, 20..=1000).text("Samples"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut config.connection_radius, 1.0..=15.0)
+                            .text("Connection radius"),
+                    );
+                } else {
+                    ui.add(egui::Slider::new(&mut config.step_size, 0.5..=6.0).text("Step size"));
+                    ui.add(egui::Slider::new(&mut config.goal_bias, 0.0..=0.5).text("Goal bias"));
+                    if self.sampling_planner == SamplingPlanner::RrtStar {
+                        ui.add(
+                            egui::Slider::new(&mut config.rewire_radius, 1.0..=10.0)
+                                .text("Rewire radius"),
+                        );
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut config.max_iterations, 100..=10000)
+                            .logarithmic(true)
+                            .text("Max iterations"),
+                    );
+                }
+                ui.add(egui::Slider::new(&mut config.batch, 1..=100).text("Samples per step"));
+
+                ui.horizontal(|ui| {
+                    if self.sampling_planner == SamplingPlanner::Prm {
+                        if ui.button("🔨 Build Roadmap").clicked() {
+                            self.build_roadmap();
+                        }
+                        let built = self.roadmap.as_ref().is_some_and(|r| !r.is_running());
+                        if ui
+                            .add_enabled(built, egui::Button::new("🔍 Query"))
+                            .clicked()
+                        {
+                            self.clear_visualization();
+                            self.query_roadmap();
+                        }
+                    } else if ui.button("▶ Grow Tree").clicked() {
+                        self.start_tree_run();
+                    }
+                    if self.step_by_step
+                        && self.simulations_running()
+                        && ui.button("⏭ Step").clicked()
+                    {
+                        self.step_simulations();
+                    }
+                    if self.simulations_running() && ui.button("⏩ Finish").clicked() {
+                        if let Some(run) = &mut self.tree_run {
+                            run.finish(&self.grid);
+                        }
+                        if let Some(roadmap) = &mut self.roadmap
+                            && roadmap.is_running()
+                        {
+                            roadmap.finish(&self.grid);
+                            self.query_roadmap();
+                        }
+                    }
+                });
+                if let Some(error) = &self.sampling_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+
+                if let Some(run) = &self.tree_run {
+                    ui.separator();
+                    ui.label(format!(
+                        "{} iterations, {} nodes",
+                        run.iterations,
+                        run.nodes.len()
+                    ));
+                    match run.path_length() {
+                        Some(length) => ui.label(
+                            egui::RichText::new(format!("Path length {length:.2} cells"))
+                                .strong()
+                                .color(self.theme.success),
+                        ),
+                        None if run.is_running() => ui.label("Searching…"),
+                        None => ui.label(
+                            egui::RichText::new("No path within the iteration budget")
+                                .color(self.theme.warning),
+                        ),
+                    };
+                }
+                if let Some(roadmap) = &self.roadmap {
+                    ui.separator();
+                    ui.label(format!(
+                        "Roadmap: {} nodes, {} edges ({} samples drawn), {} queries",
+                        roadmap.nodes.len(),
+                        roadmap.edge_count(),
+                        roadmap.attempts,
+                        roadmap.queries
+                    ));
+                    if let Some(path) = &self.roadmap_path {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Path length {:.2} cells",
+                                smoothing::polyline_length(path)
+                            ))
+                            .strong()
+                            .color(self.theme.success),
+                        );
+                    }
+                    if roadmap.is_stale(&self.grid) {
+                        ui.label(
+                            egui::RichText::new("Obstacles changed since the roadmap was built")
+                                .color(self.theme.warning),
+                        );
+                    }
+                    if ui.button("🗑 Discard Roadmap").clicked() {
+                        self.roadmap = None;
+                        self.roadmap_path = None;
+                    }
+                }
+            });
+    }
+
+    fn render_hybrid_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🚙 Hybrid A*")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.hybrid_config.max_steering_deg, 10.0..=45.0)
+                        .step_by(1.0)
+                        .text("Max steering (°)"),
+                );
+                ui.label(format!(
+                    "Turning radius {:.2} cells (wheelbase {})",
+                    self.hybrid_config.turning_radius(),
+                    hybrid_astar::WHEELBASE
+                ));
+                ui.checkbox(&mut self.hybrid_config.allow_reverse, "Allow reverse");
+                ui.add(
+                    egui::Slider::new(&mut self.hybrid_config.analytic_interval, 1..=50)
+                        .text("Reeds-Shepp shot every N expansions"),
+                );
+                for (label, heading) in [
+                    ("Start heading:", &mut self.hybrid_start_heading),
+                    ("Goal heading:", &mut self.hybrid_goal_heading),
+                ] {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(label);
+                        for option in Heading::ALL {
+                            ui.selectable_value(heading, option, option.name());
+                        }
+                    });
+                }
+                ui.checkbox(&mut self.show_hybrid_tree, "Show search tree");
+                if ui.button("▶ Plan Manoeuvre").clicked() {
+                    self.plan_hybrid();
+                }
+                if let Some(error) = &self.hybrid_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+                if let Some(plan) = &self.hybrid_plan {
+                    ui.label(format!(
+                        "Length {:.1} cells, {} direction changes, {} nodes expanded",
+                        plan.length, plan.direction_changes, plan.expanded
+                    ));
+                    ui.label(
+                        egui::RichText::new(
+                            "Blue: forward, orange: reverse, green: Reeds-Shepp shot",
+                        )
+                        .size(10.0)
+                        .italics(),
+                    );
+                }
+            });
+    }
+
+    fn render_lattice_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🚗 Lattice Planner")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Searches (x, y, heading) with straight moves and quarter-circle arcs, so the result is drivable by a car-like robot",
+                    )
+                    .size(10.0)
+                    .italics(),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.lattice_radius, 1..=5).text("Turning radius (cells)"),
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Start heading:");
+                    ui.selectable_value(&mut self.lattice_start_heading, None, "Any");
+                    for heading in lattice::HEADINGS {
+                        ui.selectable_value(
+                            &mut self.lattice_start_heading,
+                            Some(heading),
+                            heading.name(),
+                        );
+                    }
+                });
+                if ui.button("▶ Plan Trajectory").clicked() {
+                    self.plan_lattice();
+                }
+                if let Some(error) = &self.lattice_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+                if let Some(plan) = &self.lattice_plan {
+                    ui.label(format!(
+                        "Length {:.1} cells, {} primitives, {} states expanded",
+                        plan.length,
+                        plan.poses.len() - 1,
+                        plan.expanded
+                    ));
+                }
+            });
+    }
+
+    fn render_turning_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🧭 Turning")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.turn_penalties.per_45, 0.0..=5.0)
+                        .step_by(0.1)
+                        .text("Penalty per 45° (cells)"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.turn_penalties.per_90, 0.0..=10.0)
+                        .step_by(0.1)
+                        .text("Penalty per 90° (cells)"),
+                );
+                ui.checkbox(&mut self.show_heading_arrows, "Show heading arrows");
+                if self.current_algorithm != Algorithm::HeadingAStar {
+                    ui.label(
+                        egui::RichText::new("Only Heading A* minimises turning cost")
+                            .size(10.0)
+                            .italics(),
+                    );
+                }
+
+                let headings = heading::path_headings(&self.final_path);
+                if headings.is_empty() {
+                    return;
+                }
+                let turns: Vec<(Heading, Heading)> = headings
+                    .windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .filter(|(from, to)| from != to)
+                    .collect();
+                let degrees: i32 = turns.iter().map(|(from, to)| from.steps_to(*to) * 45).sum();
+                let cost: i32 = turns
+                    .iter()
+                    .map(|(from, to)| self.turn_penalties.cost(*from, *to))
+                    .sum();
+                ui.label(format!(
+                    "Path: {} turns, {}° total rotation, turn cost {:.1} cells",
+                    turns.len(),
+                    degrees,
+                    cost as f32 / heading::STRAIGHT_COST as f32
+                ));
+            });
+    }
+
+    fn paint_smoothed_path(&self, painter: &egui::Painter, view: &Viewport) {
+        if self.smoothed_path.len() < 2 {
+            return;
+        }
+        let points: Vec<egui::Pos2> = self
+            .smoothed_path
+            .iter()
+            .map(|p| view.grid_to_screen(*p))
+            .collect();
+        let width = (view.cell_size * 0.15).clamp(1.5, 4.0);
+        painter.add(egui::Shape::line(
+            points.clone(),
+            egui::Stroke::new(width, self.theme.primary_active),
+        ));
+        if self.smoothing.smoothing == Smoothing::None {
+            for point in points {
+                painter.circle_filled(point, width * 1.2, self.theme.primary_active);
+            }
+        }
+    }
+
+    fn render_smoothing_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("〰 Path Smoothing")
+            .default_open(false)
+            .show(ui, |ui| {
+                let before = self.smoothing;
+                ui.checkbox(
+                    &mut self.smoothing.remove_redundant,
+                    "Remove redundant waypoints",
+                );
+                ui.checkbox(
+                    &mut self.smoothing.string_pull,
+                    "String pulling (line of sight)",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Curve:");
+                    for smoothing in Smoothing::ALL {
+                        ui.selectable_value(
+                            &mut self.smoothing.smoothing,
+                            smoothing,
+                            smoothing.name(),
+                        );
+                    }
+                });
+                if self.smoothing.smoothing == Smoothing::Chaikin {
+                    ui.add(
+                        egui::Slider::new(&mut self.smoothing.iterations, 1..=6).text("Iterations"),
+                    );
+                }
+                if self.smoothing != before {
+                    self.update_smoothed_path();
+                }
+
+                if self.final_path.len() < 2 {
+                    ui.label("Find a path to smooth it");
+                    return;
+                }
+                let original: Vec<egui::Vec2> =
+                    self.final_path.iter().map(smoothing::cell_center).collect();
+                let smoothed = &self.smoothed_path;
+                egui::Grid::new("smoothing_stats")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label(egui::RichText::new("Original").strong());
+                        ui.label(egui::RichText::new("Smoothed").strong());
+                        ui.end_row();
+                        for (name, value) in [
+                            (
+                                "Length",
+                                smoothing::polyline_length as fn(&[egui::Vec2]) -> f32,
+                            ),
+                            ("Turning (°)", |p: &[egui::Vec2]| {
+                                smoothing::total_turning(p).to_degrees()
+                            }),
+                            ("Curvature (°/cell)", |p: &[egui::Vec2]| {
+                                smoothing::total_turning(p).to_degrees()
+                                    / smoothing::polyline_length(p).max(f32::EPSILON)
+                            }),
+                        ] {
+                            ui.label(name);
+                            ui.label(format!("{:.1}", value(&original)));
+                            ui.label(format!("{:.1}", value(smoothed)));
+                            ui.end_row();
+                        }
+                        ui.label("Vertices");
+                        ui.label(original.len().to_string());
+                        ui.label(smoothed.len().to_string());
+                        ui.end_row();
+                    });
+                if !smoothing::polyline_is_clear(&self.grid, smoothed) {
+                    ui.label(
+                        egui::RichText::new("Smoothed path cuts through an obstacle")
+                            .color(self.theme.warning),
+                    );
+                }
+            });
+    }
+
+    /// A horizontal colour bar showing `Theme::gradient` from 0 to 1.
+    fn paint_gradient_bar(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 16.0), egui::Sense::hover());
+        let segments = 24;
+        let segment_width = rect.width() / segments as f32;
+        for i in 0..segments {
+            let segment = egui::Rect::from_min_size(
+                rect.min + egui::vec2(i as f32 * segment_width, 0.0),
+                egui::vec2(segment_width + 0.5, rect.height()),
+            );
+            ui.painter().rect_filled(
+                segment,
+                0.0,
+                Theme::gradient(i as f32 / (segments - 1) as f32),
+            );
+        }
+        ui.painter().rect_stroke(
+            rect,
+            2.0,
+            egui::Stroke::new(1.0, self.theme.border),
+            egui::StrokeKind::Middle,
+        );
+    }
+
+    fn render_mapf_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🚚 Multi-Agent (MAPF)")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label("Use the Agent tool: click a start, then a goal");
+                let mut remove = None;
+                for (i, agent) in self.agents.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::Vec2::splat(12.0), egui::Sense::hover());
+                        ui.painter().circle_filled(
+                            rect.center(),
+                            6.0,
+                            AGENT_COLORS[i % AGENT_COLORS.len()],
+                        );
+                        ui.label(format!(
+                            "Agent {}: ({}, {}) → ({}, {})",
+                            i + 1,
+                            agent.start.x,
+                            agent.start.y,
+                            agent.goal.x,
+                            agent.goal.y
+                        ));
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    self.agents.remove(i);
+                    self.mapf = None;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Solver:");
+                    for solver in MapfSolver::ALL {
+                        ui.selectable_value(&mut self.mapf_solver, solver, solver.name());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.agents.is_empty(), egui::Button::new("▶ Solve"))
+                        .clicked()
+                    {
+                        self.solve_mapf();
+                    }
+                    if self.step_by_step
+                        && self.simulations_running()
+                        && ui.button("⏭ Step").clicked()
+                    {
+                        self.step_simulations();
+                    }
+                    if ui.button("🗑 Clear Agents").clicked() {
+                        self.agents.clear();
+                        self.pending_agent_start = None;
+                        self.mapf = None;
+                    }
+                });
+                if let Some(error) = &self.mapf_error {
+                    ui.label(egui::RichText::new(error).color(self.theme.warning));
+                }
+
+                let Some(run) = &self.mapf else {
+                    return;
+                };
+                ui.separator();
+                let paths = &run.solution.paths;
+                ui.horizontal(|ui| {
+                    ui.label("Time:");
+                    ui.label(
+                        egui::RichText::new(format!("{} / {}", run.time, mapf::makespan(paths)))
+                            .strong(),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Sum of Costs:");
+                    ui.label(
+                        egui::RichText::new(format!("{}", mapf::sum_of_costs(paths)))
+                            .strong()
+                            .color(self.theme.success),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Conflicts:");
+                    let count = run.solution.conflicts.len();
+                    ui.label(egui::RichText::new(format!("{count}")).strong().color(
+                        if count == 0 {
+                            self.theme.success
+                        } else {
+                            self.theme.warning
+                        },
+                    ));
+                });
+                if run.solver == MapfSolver::Cbs {
+                    ui.horizontal(|ui| {
+                        ui.label("CBS Nodes Expanded:");
+                        ui.label(
+                            egui::RichText::new(format!("{}", run.solution.expanded_nodes))
+                                .strong(),
+                        );
+                    });
+                }
+            });
+    }
+
+    fn render_robot_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🤖 Robot")
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut radius = self.grid.robot_radius();
+                if ui
+                    .add(
+                        egui::Slider::new(&mut radius, 0.0..=5.0)
+                            .step_by(0.5)
+                            .text("Footprint radius (cells)"),
+                    )
+                    .changed()
+                {
+                    self.grid.set_robot_radius(radius);
+                    self.hierarchy = None;
+                    self.clear_visualization();
+                }
+                let blocked_endpoint = [self.start_pos, self.goal_pos]
+                    .into_iter()
+                    .flatten()
+                    .any(|pos| self.grid.is_inflated(&pos));
+                if blocked_endpoint {
+                    ui.label(
+                        egui::RichText::new("Start or goal is inside the inflated zone")
+                            .color(self.theme.warning),
+                    );
+                }
+                ui.separator();
+
+                if !self.robot.has_path() {
+                    ui.label("Find a path to drive the robot along it");
+                    return;
+                }
+                ui.add(
+                    egui::Slider::new(&mut self.robot.speed, 0.5..=20.0)
+                        .text("Speed (cells/s)")
+                        .logarithmic(true),
+                );
+                ui.horizontal(|ui| {
+                    let play_label = if self.robot.playing {
+                        "⏸ Pause"
+                    } else {
+                        "▶ Play"
+                    };
+                    if ui.button(play_label).clicked() {
+                        if !self.robot.playing && self.robot.is_finished() {
+                            self.robot.reset();
+                        }
+                        self.robot.playing = !self.robot.playing;
+                    }
+                    if ui.button("⏮ Reset").clicked() {
+                        self.robot.reset();
+                    }
+                });
+            });
+    }
+
+    fn handle_zoom_and_pan(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        // Middle-drag pans the view
+        if response.dragged_by(egui::PointerButton::Middle) {
+            self.pan_offset += response.drag_delta();
+        }
+
+        // Mouse wheel zooms around the cursor
+        if let Some(hover_pos) = response.hover_pos() {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                let view = Viewport::new(
+                    response.rect.min + self.pan_offset,
+                    self.grid.size,
+                    self.grid.topology,
+                );
+                let new_size = (self.grid.size * (scroll * 0.005).exp())
+                    .clamp(viewport::MIN_CELL_SIZE, viewport::MAX_CELL_SIZE);
+                self.pan_offset = view.zoomed_origin(hover_pos, new_size) - response.rect.min;
+                self.grid.size = new_size;
+            }
+        }
+    }
+
+    /// Draws a downsampled overview in the bottom-right corner of the grid
+    /// area. Returns true when the minimap consumed a click.
+    fn render_minimap(
+        &mut self,
+        ui: &egui::Ui,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        view: &Viewport,
+    ) -> bool {
+        const MINIMAP_MAX: f32 = 180.0;
+        const MINIMAP_SAMPLES: usize = 120;
+
+        let (width, height) = (self.grid.width, self.grid.height);
+        if width == 0 || height == 0 {
+            return false;
+        }
+        let scale = MINIMAP_MAX / width.max(height) as f32;
+        let map_size = egui::vec2(width as f32 * scale, height as f32 * scale);
+        let map_rect =
+            egui::Rect::from_min_size(rect.max - map_size - egui::vec2(10.0, 10.0), map_size);
+
+        painter.rect_filled(map_rect.expand(2.0), 2.0, self.theme.surface);
+
+        // Sample one cell per block so very large grids stay cheap to draw
+        let step = width.max(height).div_ceil(MINIMAP_SAMPLES).max(1);
+        for y in (0..height).step_by(step) {
+            for x in (0..width).step_by(step) {
+                let pos = view.cell(x as i32, y as i32);
+                let cell_type = self.grid.get_cell(&pos);
+                if cell_type == CellType::Empty {
+                    continue;
+                }
+                let block = egui::Rect::from_min_size(
+                    map_rect.min + egui::vec2(x as f32, y as f32) * scale,
+                    egui::Vec2::splat(step as f32 * scale),
+                )
+                .intersect(map_rect);
+                painter.rect_filled(block, 0.0, cell_type.color());
+            }
+        }
+        for (pos, color) in [
+            (self.start_pos, CellType::Start.color()),
+            (self.goal_pos, CellType::Goal.color()),
+        ] {
+            if let Some(pos) = pos.filter(|pos| pos.floor == view.floor) {
+                let center =
+                    map_rect.min + egui::vec2(pos.x as f32 + 0.5, pos.y as f32 + 0.5) * scale;
+                painter.circle_filled(center, 3.0, color);
+            }
+        }
+
+        // Outline of the currently visible area
+        let visible_min = view.screen_to_grid(rect.min);
+        let visible_max = view.screen_to_grid(rect.max);
+        let visible_rect = egui::Rect::from_min_max(
+            map_rect.min + visible_min * scale,
+            map_rect.min + visible_max * scale,
+        )
+        .intersect(map_rect);
+        painter.rect_stroke(
+            map_rect,
+            0.0,
+            egui::Stroke::new(1.0, self.theme.border),
+            egui::StrokeKind::Outside,
+        );
+        painter.rect_stroke(
+            visible_rect,
+            0.0,
+            egui::Stroke::new(1.5, self.theme.accent),
+            egui::StrokeKind::Middle,
+        );
+
+        // Clicking or dragging on the minimap recenters the view there
+        let pointer = ui.input(|i| {
+            (i.pointer.primary_down() || i.pointer.primary_clicked())
+                .then_some(i.pointer.interact_pos())
+                .flatten()
+        });
+        if let Some(pointer) = pointer
+            && map_rect.contains(pointer)
+        {
+            let target = (pointer - map_rect.min) / scale;
+            let center_offset = rect.center() - rect.min;
+            self.pan_offset = center_offset - (view.grid_to_screen(target) - view.origin);
+            return true;
+        }
+        false
+    }
+
+    fn render_grid_size_settings(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📐 Grid Size")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(format!(
+                    "Current: {} × {}",
+                    self.grid.width, self.grid.height
+                ));
+                let mut topology = self.grid.topology;
+                egui::ComboBox::from_label("Cells")
+                    .selected_text(topology.name())
+                    .show_ui(ui, |ui| {
+                        for option in Topology::ALL {
+                            ui.selectable_value(&mut topology, option, option.name())
+                                .on_hover_text(option.description());
+                        }
+                    });
+                if topology != self.grid.topology {
+                    self.set_topology(topology);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Width:");
+                    ui.add(egui::DragValue::new(&mut self.resize_width).range(1..=2000));
+                    ui.label("Height:");
+                    ui.add(egui::DragValue::new(&mut self.resize_height).range(1..=2000));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Anchor:");
+                    ui.selectable_value(&mut self.resize_anchor, ResizeAnchor::TopLeft, "Top-left");
+                    ui.selectable_value(&mut self.resize_anchor, ResizeAnchor::Center, "Centre");
+                });
+                ui.checkbox(
+                    &mut self.clamp_endpoints,
+                    "Move start/goal to the nearest free cell (otherwise drop)",
+                );
+                if ui.button("Apply Size").clicked() {
+                    self.resize_grid();
+                }
+
+                ui.separator();
+                match self.selection {
+                    Some((a, b)) => {
+                        ui.label(format!(
+                            "Selection: {} × {}",
+                            (a.x - b.x).abs() + 1,
+                            (a.y - b.y).abs() + 1
+                        ));
+                    }
+                    None => {
+                        ui.label("Use the Select tool to drag out an area");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            self.selection.is_some(),
+                            egui::Button::new("✂ Crop to Selection"),
+                        )
+                        .clicked()
+                    {
+                        self.crop_to_selection();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.selection.is_some(),
+                            egui::Button::new("Clear Selection"),
+                        )
+                        .clicked()
+                    {
+                        self.selection = None;
+                    }
+                });
+            });
+    }
+
+    fn render_legend(&self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(egui::RichText::new("Legend").strong());
+                let mut legend_items = vec![
+                    ("Empty", CellType::Empty.color()),
+                    ("Obstacle", CellType::Obstacle.color()),
+                    ("Start", CellType::Start.color()),
+                    ("Goal", CellType::Goal.color()),
+                    ("Path", CellType::Path.color()),
+                    ("Visited", CellType::Visited.color()),
+                    ("Frontier", CellType::Frontier.color()),
+                    ("Current", CellType::Current.color()),
+                ];
+                if self.grid.floor_count() > 1 {
+                    for connector in [Connector::Stairs, Connector::Elevator] {
+                        legend_items.push((connector.name(), connector.color()));
+                    }
+                }
+
+                if self.show_visit_order && self.visit_order_style == VisitOrderStyle::Heatmap {
+                    let last_step = self
+                        .pathfinding_state
+                        .as_ref()
+                        .map_or(0, |state| state.step_count());
+                    ui.separator();
+                    ui.label("Expanded: 1");
+                    self.paint_gradient_bar(ui);
+                    ui.label(format!("{}", last_step.max(1)));
+                    ui.separator();
+                }
+
+                if self.show_distance_field
+                    && let Some(max_distance) = self
+                        .distance_cache
+                        .cached_max(&self.grid, self.current_floor)
+                {
+                    ui.separator();
+                    ui.label("Clearance: 0");
+                    self.paint_gradient_bar(ui);
+                    ui.label(format!("{max_distance:.1} cells"));
+                    ui.separator();
+                }
+
+                for (name, color) in legend_items {
+                    ui.horizontal(|ui| {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::Vec2::splat(16.0), egui::Sense::hover());
+                        ui.painter().rect_filled(rect, 2.0, color);
+                        ui.painter().rect_stroke(
+                            rect,
+                            2.0,
+                            egui::Stroke::new(1.0, self.theme.border),
+                            egui::StrokeKind::Middle,
+                        );
+                        ui.label(name);
+                    });
+                }
+            });
+        });
+    }
+
+    fn render_statistics(&self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📊 Statistics")
+            .default_open(false)
+            .show(ui, |ui| {
+                if let Some(comparison) = &self.comparison {
+                    egui::Grid::new("comparison_table")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in
+                                ["Algorithm", "Steps", "Expanded", "Peak Frontier", "Cost"]
+                            {
+                                ui.label(egui::RichText::new(header).strong());
+                            }
+                            ui.end_row();
+
+                            for run in &comparison.runs {
+                                ui.label(run.algorithm.name());
+                                ui.label(format!("{}", run.state.step_count()));
+                                ui.label(format!("{}", run.state.closed_set_len()));
+                                ui.label(format!("{}", run.peak_frontier));
+                                match (run.status, run.path_cost()) {
+                                    (RunStatus::Found, Some(cost)) => ui.label(
+                                        egui::RichText::new(format!("{:.1}", cost))
+                                            .color(self.theme.success),
+                                    ),
+                                    (RunStatus::NoPath, _) => ui.label(
+                                        egui::RichText::new("no path").color(self.theme.warning),
+                                    ),
+                                    _ => ui.label("–"),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                } else if let Some(state) = &self.pathfinding_state {
+                    ui.horizontal(|ui| {
+                        ui.label("Steps:");
+                        ui.label(egui::RichText::new(format!("{}", state.step_count())).strong());
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Frontier Size:");
+                        ui.label(egui::RichText::new(format!("{}", self.frontier_len())).strong());
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Visited Nodes:");
+                        ui.label(
+                            egui::RichText::new(format!("{}", state.closed_set_len())).strong(),
+                        );
+                    });
+
+                    if !self.final_path.is_empty() {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Path Length:");
+                            ui.label(
+                                egui::RichText::new(format!("{}", self.final_path.len()))
+                                    .strong()
+                                    .color(self.theme.success),
+                            );
+                        });
+
+                        if let Some(path_cost) = self
+                            .final_path
+                            .last()
+                            .and_then(|pos| state.g_cost(pos))
+                            .map(|cost| self.current_algorithm.cost_in_cells(cost))
+                        {
+                            ui.horizontal(|ui| {
+                                ui.label("Path Cost:");
+                                ui.label(
+                                    egui::RichText::new(format!("{:.1}", path_cost))
+                                        .strong()
+                                        .color(self.theme.success),
+                                );
+                            });
+                        }
+                    }
+                } else if self.frontier_explorer.is_none() && self.tour.is_none() {
+                    ui.label("No pathfinding in progress");
+                }
+
+                if let Some(tour) = &self.tour {
+                    ui.label(egui::RichText::new("Tour").strong());
+                    egui::Grid::new("tour_legs").striped(true).show(ui, |ui| {
+                        for header in ["Leg", "From", "To", "Cost"] {
+                            ui.label(egui::RichText::new(header).strong());
+                        }
+                        ui.end_row();
+                        for (i, leg) in tour.legs.iter().enumerate() {
+                            ui.label(format!("{}", i + 1));
+                            ui.label(format!("({}, {})", leg.from.x, leg.from.y));
+                            ui.label(format!("({}, {})", leg.to.x, leg.to.y));
+                            ui.label(format!("{}", leg.cost()));
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Total Cost:");
+                        ui.label(
+                            egui::RichText::new(format!("{}", tour.cost()))
+                                .strong()
+                                .color(self.theme.success),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Order:");
+                        ui.label(if self.waypoints_ordered {
+                            "fixed"
+                        } else if tour.exact {
+                            "optimal (Held-Karp)"
+                        } else {
+                            "heuristic (NN + 2-opt)"
+                        });
+                    });
+                }
+
+                if let Some(explorer) = &self.frontier_explorer {
+                    ui.horizontal(|ui| {
+                        ui.label("Explored Area:");
+                        ui.label(
+                            egui::RichText::new(format!("{:.1}%", explorer.explored_percent()))
+                                .strong()
+                                .color(self.theme.success),
+                        );
+                    });
+                    self.render_history_chart(ui, &explorer.coverage_history, 100.0);
+                }
+            });
+    }
+
+    /// Small line chart of `values` (0..=`max`) over time.
+    fn render_history_chart(&self, ui: &mut egui::Ui, values: &[f32], max: f32) {
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width(), 80.0), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, self.theme.surface);
+        painter.rect_stroke(
+            rect,
+            2.0,
+            egui::Stroke::new(1.0, self.theme.border),
+            egui::StrokeKind::Inside,
+        );
+        if values.len() < 2 {
+            return;
+        }
+        let last = (values.len() - 1) as f32;
+        let points: Vec<egui::Pos2> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                egui::pos2(
+                    rect.left() + rect.width() * i as f32 / last,
+                    rect.bottom() - rect.height() * (value / max).clamp(0.0, 1.0),
+                )
+            })
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.5, self.theme.primary),
+        ));
+        painter.text(
+            rect.left_top() + egui::vec2(4.0, 2.0),
+            egui::Align2::LEFT_TOP,
+            format!("{max:.0}%"),
+            egui::FontId::proportional(9.0),
+            self.theme.text_primary,
+        );
+        painter.text(
+            rect.right_bottom() - egui::vec2(4.0, 2.0),
+            egui::Align2::RIGHT_BOTTOM,
+            format!("{} steps", values.len() - 1),
+            egui::FontId::proportional(9.0),
+            self.theme.text_primary,
+        );
     }
 
     fn render_inspector(&self, ui: &mut egui::Ui) {
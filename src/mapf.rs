@@ -0,0 +1,478 @@
+use crate::{grid::Grid, position::Position};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// High-level CBS nodes expanded before giving up.
+const CBS_NODE_LIMIT: usize = 5000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MapfSolver {
+    Independent,
+    Prioritized,
+    Cbs,
+}
+
+impl MapfSolver {
+    pub const ALL: [MapfSolver; 3] = [Self::Independent, Self::Prioritized, Self::Cbs];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Independent => "Independent",
+            Self::Prioritized => "Prioritized",
+            Self::Cbs => "CBS",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Independent => {
+                "Each agent plans its own shortest path and ignores the others. Fast, but agents collide; the conflicts are highlighted so you can see what the coordinated solvers have to resolve."
+            }
+            Self::Prioritized => {
+                "Prioritized planning plans agents one at a time in list order. Each agent runs space-time A* around a reservation table holding the earlier agents' paths. Fast, but incomplete: a bad priority order can make later agents fail."
+            }
+            Self::Cbs => {
+                "Conflict-Based Search plans agents independently, then finds the first vertex or edge conflict and branches into two children that each forbid one agent from the conflicting move. Searching the constraint tree by total cost gives optimal sum-of-costs solutions."
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Agent {
+    pub start: Position,
+    pub goal: Position,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Conflict {
+    // Two agents in the same cell at time t
+    Vertex {
+        a: usize,
+        b: usize,
+        pos: Position,
+        t: usize,
+    },
+    // Two agents swapping cells between t-1 and t
+    Edge {
+        a: usize,
+        b: usize,
+        from: Position,
+        to: Position,
+        t: usize,
+    },
+}
+
+impl Conflict {
+    pub fn time(&self) -> usize {
+        match self {
+            Self::Vertex { t, .. } | Self::Edge { t, .. } => *t,
+        }
+    }
+
+    pub fn cells(&self) -> Vec<Position> {
+        match self {
+            Self::Vertex { pos, .. } => vec![*pos],
+            Self::Edge { from, to, .. } => vec![*from, *to],
+        }
+    }
+}
+
+/// Forbidden moves for one agent in space-time A*.
+#[derive(Clone, Default)]
+pub struct Constraints {
+    vertex: HashSet<(Position, usize)>,
+    edge: HashSet<(Position, Position, usize)>,
+    // Cells occupied forever from the given time (agents resting at goals)
+    permanent: HashMap<Position, usize>,
+}
+
+impl Constraints {
+    fn allows(&self, from: Position, to: Position, t: usize) -> bool {
+        !self.vertex.contains(&(to, t))
+            && !self.edge.contains(&(from, to, t))
+            && self.permanent.get(&to).is_none_or(|&since| t < since)
+    }
+
+    /// Time after which nothing is constrained any more.
+    fn latest_time(&self) -> usize {
+        let vertex = self.vertex.iter().map(|(_, t)| *t);
+        let edge = self.edge.iter().map(|(_, _, t)| *t);
+        let permanent = self.permanent.values().copied();
+        vertex.chain(edge).chain(permanent).max().unwrap_or(0)
+    }
+
+    /// Latest time at which `pos` is constrained; an agent may only finish
+    /// at its goal after this.
+    fn last_constraint_at(&self, pos: Position) -> usize {
+        self.vertex
+            .iter()
+            .filter(|(p, _)| *p == pos)
+            .map(|(_, t)| *t)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Reserves every cell and move of `path` for lower-priority agents.
+    fn reserve(&mut self, path: &[Position]) {
+        for (t, &pos) in path.iter().enumerate() {
+            self.vertex.insert((pos, t));
+            if t > 0 {
+                self.edge.insert((pos, path[t - 1], t));
+            }
+        }
+        if let Some(&last) = path.last() {
+            self.permanent.insert(last, path.len() - 1);
+        }
+    }
+}
+
+/// Time-expanded A* over (position, t) with a wait action. The path stays at
+/// the goal once it arrives, so `path[t]` is the position at time `t`.
+pub fn space_time_astar(
+    grid: &Grid,
+    start: Position,
+    goal: Position,
+    constraints: &Constraints,
+) -> Option<Vec<Position>> {
+    // Waiting never helps reach a goal that is walled off, so fail before
+    // searching the time-expanded graph
+    let reachable = grid.reachable_cells(start);
+    if !reachable.contains(&goal) {
+        return None;
+    }
+    // Past the last constraint the grid is static, so any remaining route
+    // visits each reachable cell at most once
+    let horizon = constraints.latest_time() + reachable.len();
+    let earliest_finish = constraints.last_constraint_at(goal);
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<(Position, usize), (Position, usize)> = HashMap::new();
+    let mut closed = HashSet::new();
+    let h = |pos: Position| grid.distance(&pos, &goal) as usize;
+    open.push(Reverse((h(start), 0usize, start.x, start.y)));
+
+    while let Some(Reverse((_, t, x, y))) = open.pop() {
+        let pos = Position::new(x, y);
+        if !closed.insert((pos, t)) {
+            continue;
+        }
+        if pos == goal && t >= earliest_finish {
+            let mut path = vec![pos];
+            let mut key = (pos, t);
+            while let Some(&parent) = came_from.get(&key) {
+                path.push(parent.0);
+                key = parent;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if t >= horizon {
+            continue;
+        }
+
+        let moves = grid
+            .get_walkable_neighbors(&pos)
+            .into_iter()
+            .chain(std::iter::once(pos));
+        for next in moves {
+            let next_t = t + 1;
+            if closed.contains(&(next, next_t)) || !constraints.allows(pos, next, next_t) {
+                continue;
+            }
+            came_from.entry((next, next_t)).or_insert((pos, t));
+            open.push(Reverse((next_t + h(next), next_t, next.x, next.y)));
+        }
+    }
+    None
+}
+
+/// Position of an agent at time `t`, resting at the end once it arrives.
+pub fn position_at(path: &[Position], t: usize) -> Position {
+    path[t.min(path.len() - 1)]
+}
+
+/// Sum over agents of the time each one reaches its goal for good.
+pub fn sum_of_costs(paths: &[Vec<Position>]) -> usize {
+    paths.iter().map(|path| path.len().saturating_sub(1)).sum()
+}
+
+pub fn makespan(paths: &[Vec<Position>]) -> usize {
+    paths
+        .iter()
+        .map(|path| path.len().saturating_sub(1))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Every vertex and edge conflict between the given paths, in time order.
+pub fn find_conflicts(paths: &[Vec<Position>]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    for t in 0..=makespan(paths) {
+        for a in 0..paths.len() {
+            for b in a + 1..paths.len() {
+                let (pa, pb) = (position_at(&paths[a], t), position_at(&paths[b], t));
+                if pa == pb {
+                    conflicts.push(Conflict::Vertex { a, b, pos: pa, t });
+                } else if t > 0
+                    && pa == position_at(&paths[b], t - 1)
+                    && pb == position_at(&paths[a], t - 1)
+                {
+                    conflicts.push(Conflict::Edge {
+                        a,
+                        b,
+                        from: pb,
+                        to: pa,
+                        t,
+                    });
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+pub struct MapfSolution {
+    pub paths: Vec<Vec<Position>>,
+    pub conflicts: Vec<Conflict>,
+    pub expanded_nodes: usize,
+}
+
+pub enum MapfError {
+    NoPath(usize),
+    // Two agents placed on the same start or the same goal collide at
+    // t = 0 or forever after arriving, so no solver can separate them
+    SharedStart(usize, usize),
+    SharedGoal(usize, usize),
+    NodeLimit,
+}
+
+pub fn solve(grid: &Grid, agents: &[Agent], solver: MapfSolver) -> Result<MapfSolution, MapfError> {
+    for a in 0..agents.len() {
+        for b in a + 1..agents.len() {
+            if agents[a].start == agents[b].start {
+                return Err(MapfError::SharedStart(a, b));
+            }
+            if agents[a].goal == agents[b].goal {
+                return Err(MapfError::SharedGoal(a, b));
+            }
+        }
+    }
+    for (i, agent) in agents.iter().enumerate() {
+        if !grid.reachable_cells(agent.start).contains(&agent.goal) {
+            return Err(MapfError::NoPath(i));
+        }
+    }
+    let (paths, expanded_nodes) = match solver {
+        MapfSolver::Independent => (plan_independent(grid, agents)?, agents.len()),
+        MapfSolver::Prioritized => (plan_prioritized(grid, agents)?, agents.len()),
+        MapfSolver::Cbs => plan_cbs(grid, agents)?,
+    };
+    let conflicts = find_conflicts(&paths);
+    Ok(MapfSolution {
+        paths,
+        conflicts,
+        expanded_nodes,
+    })
+}
+
+fn plan_independent(grid: &Grid, agents: &[Agent]) -> Result<Vec<Vec<Position>>, MapfError> {
+    agents
+        .iter()
+        .enumerate()
+        .map(|(i, agent)| {
+            space_time_astar(grid, agent.start, agent.goal, &Constraints::default())
+                .ok_or(MapfError::NoPath(i))
+        })
+        .collect()
+}
+
+fn plan_prioritized(grid: &Grid, agents: &[Agent]) -> Result<Vec<Vec<Position>>, MapfError> {
+    let mut reservations = Constraints::default();
+    let mut paths = Vec::new();
+    for (i, agent) in agents.iter().enumerate() {
+        let path = space_time_astar(grid, agent.start, agent.goal, &reservations)
+            .ok_or(MapfError::NoPath(i))?;
+        reservations.reserve(&path);
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+struct CbsNode {
+    constraints: Vec<Constraints>,
+    paths: Vec<Vec<Position>>,
+    cost: usize,
+}
+
+fn plan_cbs(grid: &Grid, agents: &[Agent]) -> Result<(Vec<Vec<Position>>, usize), MapfError> {
+    let paths = plan_independent(grid, agents)?;
+    let mut nodes = vec![CbsNode {
+        constraints: vec![Constraints::default(); agents.len()],
+        cost: sum_of_costs(&paths),
+        paths,
+    }];
+    // Min-heap of (cost, conflict count, node index)
+    let mut open = BinaryHeap::from([Reverse((nodes[0].cost, 0usize, 0usize))]);
+    let mut expanded = 0;
+    // Agent whose low-level search last failed, blamed if the tree runs out
+    let mut failed_agent = None;
+
+    while let Some(Reverse((_, _, index))) = open.pop() {
+        expanded += 1;
+        if expanded > CBS_NODE_LIMIT {
+            return Err(MapfError::NodeLimit);
+        }
+
+        let conflicts = find_conflicts(&nodes[index].paths);
+        let Some(conflict) = conflicts.first() else {
+            let paths = std::mem::take(&mut nodes[index].paths);
+            return Ok((paths, expanded));
+        };
+
+        // Each child forbids one of the two agents from its side of the conflict
+        let branches = match *conflict {
+            Conflict::Vertex { a, b, pos, t } => [(a, pos, None, t), (b, pos, None, t)],
+            Conflict::Edge { a, b, from, to, t } => {
+                [(a, to, Some(from), t), (b, from, Some(to), t)]
+            }
+        };
+        for (agent, to, from, t) in branches {
+            let mut constraints = nodes[index].constraints.clone();
+            match from {
+                Some(from) => constraints[agent].edge.insert((from, to, t)),
+                None => constraints[agent].vertex.insert((to, t)),
+            };
+            let Some(path) = space_time_astar(
+                grid,
+                agents[agent].start,
+                agents[agent].goal,
+                &constraints[agent],
+            ) else {
+                failed_agent = Some(agent);
+                continue;
+            };
+            let mut paths = nodes[index].paths.clone();
+            paths[agent] = path;
+            let cost = sum_of_costs(&paths);
+            let conflict_count = find_conflicts(&paths).len();
+            nodes.push(CbsNode {
+                constraints,
+                paths,
+                cost,
+            });
+            open.push(Reverse((cost, conflict_count, nodes.len() - 1)));
+        }
+    }
+    Err(failed_agent.map_or(MapfError::NodeLimit, MapfError::NoPath))
+}
+
+/// Agents stepping along their paths in lockstep.
+pub struct MapfRun {
+    pub solver: MapfSolver,
+    pub solution: MapfSolution,
+    pub time: usize,
+}
+
+impl MapfRun {
+    pub fn is_running(&self) -> bool {
+        self.time < makespan(&self.solution.paths)
+    }
+
+    pub fn step(&mut self) {
+        if self.is_running() {
+            self.time += 1;
+        }
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.solution
+            .paths
+            .iter()
+            .map(|path| position_at(path, self.time))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellType;
+
+    /// A one-cell-wide corridor along y = 1 with a side bay at (`bay`, 0).
+    fn corridor(width: usize, bay: Option<i32>) -> Grid {
+        let mut grid = Grid::new(width, 3, 10.0);
+        for x in 0..width as i32 {
+            if Some(x) != bay {
+                grid.set_cell(Position::new(x, 0), CellType::Obstacle);
+            }
+            grid.set_cell(Position::new(x, 2), CellType::Obstacle);
+        }
+        grid
+    }
+
+    fn swap_agents(width: i32) -> Vec<Agent> {
+        vec![
+            Agent {
+                start: Position::new(0, 1),
+                goal: Position::new(width - 1, 1),
+            },
+            Agent {
+                start: Position::new(width - 1, 1),
+                goal: Position::new(0, 1),
+            },
+        ]
+    }
+
+    #[test]
+    fn cbs_resolves_swap_through_a_bay() {
+        let grid = corridor(5, Some(2));
+        let Ok(solution) = solve(&grid, &swap_agents(5), MapfSolver::Cbs) else {
+            panic!("CBS found no solution");
+        };
+        assert!(solution.conflicts.is_empty());
+        // One agent steps into the bay and back out (two extra moves) while
+        // the other waits one step for the bay to be entered
+        assert_eq!(sum_of_costs(&solution.paths), 4 + 4 + 2 + 1);
+    }
+
+    #[test]
+    fn independent_paths_swap_in_a_corridor() {
+        let grid = corridor(4, None);
+        let Ok(solution) = solve(&grid, &swap_agents(4), MapfSolver::Independent) else {
+            panic!("independent planning failed");
+        };
+        assert!(!solution.conflicts.is_empty());
+    }
+
+    #[test]
+    fn cbs_gives_up_on_a_corridor_without_passing_room() {
+        let grid = corridor(4, None);
+        assert!(solve(&grid, &swap_agents(4), MapfSolver::Cbs).is_err());
+    }
+
+    #[test]
+    fn walled_off_goal_fails_without_searching() {
+        let mut grid = corridor(5, None);
+        grid.set_cell(Position::new(2, 1), CellType::Obstacle);
+        let agents = [Agent {
+            start: Position::new(0, 1),
+            goal: Position::new(4, 1),
+        }];
+        assert!(matches!(
+            solve(&grid, &agents, MapfSolver::Cbs),
+            Err(MapfError::NoPath(0))
+        ));
+    }
+
+    #[test]
+    fn shared_endpoints_are_rejected() {
+        let grid = corridor(5, None);
+        let mut agents = swap_agents(5);
+        agents[1].goal = agents[0].goal;
+        assert!(matches!(
+            solve(&grid, &agents, MapfSolver::Cbs),
+            Err(MapfError::SharedGoal(0, 1))
+        ));
+    }
+}
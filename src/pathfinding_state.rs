@@ -1,5 +1,17 @@
-use crate::{algorithms::Algorithm, grid::Grid, node::Node, position::Position};
+use crate::{
+    algorithms::Algorithm,
+    distance_field,
+    dynamic_obstacle::{self, DynamicObstacle},
+    grid::Grid,
+    heading::{self, Heading, TurnPenalties},
+    landmarks::Landmarks,
+    node::Node,
+    position::Position,
+    topology::Topology,
+};
+use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 
 #[derive(Clone, Debug, Default)]
 pub struct NeighborInfo {
@@ -16,6 +28,9 @@ pub enum StepResult {
     NoPath,
 }
 
+// Heading-aware open set entry: (f, h, x, y, heading)
+type HeadingEntry = (i32, i32, i32, i32, Heading);
+
 #[derive(Default)]
 pub struct PathfindingState {
     // A* frontier
@@ -27,6 +42,28 @@ pub struct PathfindingState {
 
     closed_set: HashSet<Position>,
     came_from: HashMap<Position, Position>,
+    // Cell shape of the grid being searched, for the distance heuristic
+    topology: Topology,
+
+    // Space-time A*: moving obstacles and the search tree over (position, t)
+    dynamic_obstacles: Vec<DynamicObstacle>,
+    time_came_from: HashMap<(Position, i32), (Position, i32)>,
+    time_closed_set: HashSet<(Position, i32)>,
+    // Last time layer expanded, set on the first step from the start's
+    // reachable area
+    time_horizon: Option<i32>,
+
+    // A*: extra cost of entering each cell near obstacles, indexed [floor][y][x]
+    clearance_penalty: Vec<Vec<Vec<i32>>>,
+    // A*: landmark distance tables for the ALT heuristic
+    landmarks: Option<Rc<Landmarks>>,
+
+    // Heading-aware A*: search over (position, heading) with turn penalties
+    turn_penalties: TurnPenalties,
+    heading_open: BinaryHeap<Reverse<HeadingEntry>>,
+    heading_g: HashMap<(Position, Heading), i32>,
+    heading_came_from: HashMap<(Position, Heading), (Position, Heading)>,
+    heading_closed: HashSet<(Position, Heading)>,
 
     // For visualizing numbers
     g_costs: HashMap<Position, i32>,
@@ -35,6 +72,8 @@ pub struct PathfindingState {
 
     current_node: Option<Position>,
     step_count: usize,
+    // Step index at which each node was first closed
+    visit_order: HashMap<Position, usize>,
 
     // Inspector: details of the last step
     last_step_info: String,
@@ -67,23 +106,32 @@ impl PathfindingState {
         Self::default()
     }
 
-    pub fn initialize(&mut self, algorithm: &Algorithm, start: Position, goal: Position) {
+    pub fn initialize(
+        &mut self,
+        algorithm: &Algorithm,
+        topology: Topology,
+        start: Position,
+        goal: Position,
+    ) {
         // Clear all state
-        *self = Self::default();
+        *self = Self {
+            topology,
+            ..Self::default()
+        };
 
         match algorithm {
-            Algorithm::AStar => {
+            Algorithm::AStar | Algorithm::SpaceTimeAStar => {
                 let start_node = Node {
                     position: start,
                     g_cost: 0,
-                    h_cost: start.manhattan_distance_to(&goal),
+                    h_cost: start.distance_to(&goal, topology),
                 };
                 self.open_set.push(start_node);
                 self.g_costs.insert(start, 0);
                 self.h_costs
-                    .insert(start, start.manhattan_distance_to(&goal));
+                    .insert(start, start.distance_to(&goal, topology));
                 self.f_costs
-                    .insert(start, start.manhattan_distance_to(&goal));
+                    .insert(start, start.distance_to(&goal, topology));
             }
             Algorithm::Bfs => {
                 self.bfs_queue.push_back(start);
@@ -97,6 +145,18 @@ impl PathfindingState {
                 // self.h_costs
                 // .insert(start, start.manhattan_distance_to(&goal));
             }
+            Algorithm::HeadingAStar => {
+                // The robot may start facing any direction
+                let h_cost = heading::octile_distance(&start, &goal);
+                for heading in Heading::ALL {
+                    self.heading_g.insert((start, heading), 0);
+                    self.heading_open
+                        .push(Reverse((h_cost, h_cost, start.x, start.y, heading)));
+                }
+                self.g_costs.insert(start, 0);
+                self.h_costs.insert(start, h_cost);
+                self.f_costs.insert(start, h_cost);
+            }
         }
     }
 
@@ -105,9 +165,56 @@ impl PathfindingState {
             Algorithm::AStar => self.step_astar(goal, grid),
             Algorithm::Bfs => self.step_bfs(goal, grid),
             Algorithm::Dfs => self.step_dfs(goal, grid),
+            Algorithm::SpaceTimeAStar => self.step_space_time_astar(goal, grid),
+            Algorithm::HeadingAStar => self.step_heading_astar(goal, grid),
         }
     }
 
+    /// Rotation costs for heading-aware A*. Call after `initialize`.
+    pub fn set_turn_penalties(&mut self, penalties: TurnPenalties) {
+        self.turn_penalties = penalties;
+    }
+
+    /// Makes A* pay extra for cells close to obstacles. Call after `initialize`.
+    pub fn set_clearance_weight(&mut self, grid: &Grid, weight: f32) {
+        self.clearance_penalty = if weight > 0.0 {
+            distance_field::clearance_penalty(grid, weight)
+        } else {
+            Vec::new()
+        };
+    }
+
+    fn clearance(&self, pos: &Position) -> i32 {
+        self.clearance_penalty
+            .get(pos.floor as usize)
+            .and_then(|floor| floor.get(pos.y as usize))
+            .map_or(0, |row| row[pos.x as usize])
+    }
+
+    /// One step, or the stairs/elevator cost when changing floors, plus the
+    /// clearance penalty of the cell entered.
+    fn step_cost(&self, grid: &Grid, from: &Position, to: &Position) -> i32 {
+        grid.move_cost(from, to) + self.clearance(to)
+    }
+
+    /// Landmarks that replace A*'s Manhattan heuristic with ALT. Call after
+    /// `initialize`.
+    pub fn set_landmarks(&mut self, landmarks: Option<Rc<Landmarks>>) {
+        self.landmarks = landmarks;
+    }
+
+    fn heuristic(&self, pos: &Position, goal: &Position) -> i32 {
+        match &self.landmarks {
+            Some(landmarks) => landmarks.heuristic(pos, goal),
+            None => pos.distance_to(goal, self.topology),
+        }
+    }
+
+    /// Moving obstacles that space-time A* must avoid. Call after `initialize`.
+    pub fn set_dynamic_obstacles(&mut self, obstacles: &[DynamicObstacle]) {
+        self.dynamic_obstacles = obstacles.to_vec();
+    }
+
     fn step_astar(&mut self, goal: Position, grid: &mut Grid) -> StepResult {
         if self.open_set.is_empty() {
             self.last_step_info = "Open set empty → no path".to_string();
@@ -118,6 +225,9 @@ impl PathfindingState {
         self.closed_set.insert(current_node.position);
         self.current_node = Some(current_node.position);
         self.step_count += 1;
+        self.visit_order
+            .entry(current_node.position)
+            .or_insert(self.step_count);
 
         if let Some(previous_node) = self.previous_node {
             grid.mark_previous_node_as_visited(previous_node);
@@ -150,23 +260,23 @@ impl PathfindingState {
             .collect::<Vec<_>>();
 
         let mut neighbors_to_add: Vec<(Position, Node)> = Vec::new();
-        let open_snapshot: Vec<Node> = self.open_set.clone().into_vec();
 
         for neighbor_pos in neighbors {
-            let tentative_g = current_node.g_cost + 1;
-            let h_cost = neighbor_pos.manhattan_distance_to(&goal);
+            let tentative_g =
+                current_node.g_cost + self.step_cost(grid, &current_node.position, &neighbor_pos);
+            let h_cost = self.heuristic(&neighbor_pos, &goal);
             let mut decision = "push".to_string();
 
+            // Every open node has its best g recorded, so no need to scan the heap
             let mut should_add = true;
-            for existing in &open_snapshot {
-                if existing.position == neighbor_pos && existing.g_cost <= tentative_g {
-                    should_add = false;
-                    decision = format!(
-                        "skip: existing g={} ≤ tentative g={}",
-                        existing.g_cost, tentative_g
-                    );
-                    break;
-                }
+            if let Some(&existing_g) = self.g_costs.get(&neighbor_pos)
+                && existing_g <= tentative_g
+            {
+                should_add = false;
+                decision = format!(
+                    "skip: existing g={} ≤ tentative g={}",
+                    existing_g, tentative_g
+                );
             }
 
             if should_add {
@@ -204,13 +314,275 @@ impl PathfindingState {
             grid.mark_frontier(&[neighbor_pos], None, None);
         }
 
-        // Mark visited cells
-        let visited: Vec<Position> = self.closed_set.iter().copied().collect();
-        grid.mark_visited(&visited, None, None);
+        StepResult::Continue
+    }
+
+    fn step_space_time_astar(&mut self, goal: Position, grid: &mut Grid) -> StepResult {
+        if self.time_horizon.is_none()
+            && let Some(start) = self.open_set.peek().map(|node| node.position)
+        {
+            // Moving obstacles never block a cell for good, so a goal outside
+            // the start's static reachable area is unreachable at any time
+            let reachable = grid.reachable_cells(start);
+            if !reachable.contains(&goal) {
+                self.open_set.clear();
+                self.last_step_info =
+                    "Goal unreachable even without moving obstacles → no path".to_string();
+                return StepResult::NoPath;
+            }
+            // Without a horizon, waiting in a sealed-off area would never end
+            let cells = reachable.len();
+            let period = dynamic_obstacle::combined_period(&self.dynamic_obstacles, cells);
+            self.time_horizon = Some((cells + period) as i32);
+        }
+
+        // The same (position, t) can be pushed more than once; skip stale entries
+        let current_node = loop {
+            let Some(node) = self.open_set.pop() else {
+                self.last_step_info = "Open set empty → no path".to_string();
+                return StepResult::NoPath;
+            };
+            if self.time_closed_set.insert((node.position, node.g_cost)) {
+                break node;
+            }
+        };
+        let position = current_node.position;
+        let t = current_node.g_cost;
+
+        self.closed_set.insert(position);
+        self.current_node = Some(position);
+        self.step_count += 1;
+        self.visit_order.entry(position).or_insert(self.step_count);
+
+        if let Some(previous_node) = self.previous_node {
+            grid.mark_previous_node_as_visited(previous_node);
+        }
+        self.previous_node = Some(position);
+        grid.mark_current(position);
+
+        self.last_step_info = format!(
+            "Step {}: pop ({}, {}) at t={}, h={}, f={} ({} open, {} closed)",
+            self.step_count,
+            position.x,
+            position.y,
+            t,
+            current_node.h_cost,
+            current_node.f_cost(),
+            self.open_set.len(),
+            self.time_closed_set.len()
+        );
+        self.last_neighbors.clear();
+
+        if position == goal {
+            // Earlier visits may have recorded a smaller g for the goal cell
+            self.g_costs.insert(position, t);
+            let path = self.reconstruct_time_path((position, t));
+            return StepResult::PathFound(path);
+        }
+
+        if self.time_horizon.is_some_and(|horizon| t >= horizon) {
+            return StepResult::Continue;
+        }
+
+        let moves = grid
+            .get_walkable_neighbors(&position)
+            .into_iter()
+            .chain(std::iter::once(position));
+        for next in moves {
+            let next_t = t + 1;
+            let h_cost = next.distance_to(&goal, self.topology);
+            let action = if next == position { "wait" } else { "move" };
+
+            let decision = if self.time_closed_set.contains(&(next, next_t)) {
+                format!("skip: already expanded at t={next_t}")
+            } else if dynamic_obstacle::collides(
+                &self.dynamic_obstacles,
+                position,
+                next,
+                t as usize,
+            ) {
+                format!("skip: moving obstacle at t={next_t}")
+            } else {
+                self.time_came_from
+                    .entry((next, next_t))
+                    .or_insert((position, t));
+                if next != position {
+                    self.came_from.entry(next).or_insert(position);
+                }
+                self.g_costs.entry(next).or_insert(next_t);
+                self.h_costs.insert(next, h_cost);
+                self.f_costs.entry(next).or_insert(next_t + h_cost);
+                self.open_set.push(Node {
+                    position: next,
+                    g_cost: next_t,
+                    h_cost,
+                });
+                grid.mark_frontier(&[next], None, None);
+                format!(
+                    "push {action}: t={}, h={}, f={}",
+                    next_t,
+                    h_cost,
+                    next_t + h_cost
+                )
+            };
+
+            self.last_neighbors.push(NeighborInfo {
+                pos: next,
+                g: Some(next_t),
+                h: Some(h_cost),
+                f: Some(next_t + h_cost),
+                decision,
+            });
+        }
+
+        StepResult::Continue
+    }
+
+    /// Follows the time-indexed parents back to t=0. Waits show up as
+    /// repeated positions, so `path[t]` is where the robot is at time `t`.
+    fn reconstruct_time_path(&self, goal: (Position, i32)) -> Vec<Position> {
+        let mut path = vec![goal.0];
+        let mut current = goal;
+        while let Some(&parent) = self.time_came_from.get(&current) {
+            path.push(parent.0);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn step_heading_astar(&mut self, goal: Position, grid: &mut Grid) -> StepResult {
+        // Skip entries superseded by a cheaper push or already expanded
+        let (g_cost, h_cost, position, heading) = loop {
+            let Some(Reverse((f_cost, h_cost, x, y, heading))) = self.heading_open.pop() else {
+                self.last_step_info = "Open set empty → no path".to_string();
+                return StepResult::NoPath;
+            };
+            let position = Position::new(x, y);
+            let g_cost = f_cost - h_cost;
+            if self.heading_g.get(&(position, heading)) == Some(&g_cost)
+                && self.heading_closed.insert((position, heading))
+            {
+                break (g_cost, h_cost, position, heading);
+            }
+        };
+
+        self.closed_set.insert(position);
+        self.current_node = Some(position);
+        self.step_count += 1;
+        self.visit_order.entry(position).or_insert(self.step_count);
+
+        if let Some(previous_node) = self.previous_node {
+            grid.mark_previous_node_as_visited(previous_node);
+        }
+        self.previous_node = Some(position);
+        grid.mark_current(position);
+
+        self.last_step_info = format!(
+            "Step {}: pop ({}, {}) facing {} with g={}, h={}, f={} ({} open, {} closed)",
+            self.step_count,
+            position.x,
+            position.y,
+            heading.name(),
+            g_cost,
+            h_cost,
+            g_cost + h_cost,
+            self.heading_open.len(),
+            self.heading_closed.len()
+        );
+        self.last_neighbors.clear();
+
+        if position == goal {
+            self.g_costs.insert(position, g_cost);
+            let path = self.reconstruct_heading_path((position, heading));
+            return StepResult::PathFound(path);
+        }
+
+        for next_heading in Heading::ALL {
+            let (dx, dy) = next_heading.offset();
+            let next = Position::new(position.x + dx, position.y + dy);
+            if !grid.is_walkable(&next) {
+                continue;
+            }
+            // Diagonal moves may not squeeze between two blocked cells
+            if next_heading.is_diagonal()
+                && (!grid.is_walkable(&Position::new(position.x + dx, position.y))
+                    || !grid.is_walkable(&Position::new(position.x, position.y + dy)))
+            {
+                continue;
+            }
+
+            let move_cost = if next_heading.is_diagonal() {
+                heading::DIAGONAL_COST
+            } else {
+                heading::STRAIGHT_COST
+            } + heading::STRAIGHT_COST * self.clearance(&next);
+            let turn_cost = self.turn_penalties.cost(heading, next_heading);
+            let tentative_g = g_cost + move_cost + turn_cost;
+            let next_h = heading::octile_distance(&next, &goal);
+            let key = (next, next_heading);
+
+            let decision = if self.heading_closed.contains(&key) {
+                format!("skip {}: already expanded", next_heading.name())
+            } else if let Some(&existing_g) = self.heading_g.get(&key)
+                && existing_g <= tentative_g
+            {
+                format!(
+                    "skip {}: existing g={} ≤ tentative g={}",
+                    next_heading.name(),
+                    existing_g,
+                    tentative_g
+                )
+            } else {
+                self.heading_g.insert(key, tentative_g);
+                self.heading_came_from.insert(key, (position, heading));
+                self.heading_open.push(Reverse((
+                    tentative_g + next_h,
+                    next_h,
+                    next.x,
+                    next.y,
+                    next_heading,
+                )));
+                if self.g_costs.get(&next).is_none_or(|&g| tentative_g < g) {
+                    self.g_costs.insert(next, tentative_g);
+                    self.f_costs.insert(next, tentative_g + next_h);
+                    self.came_from.insert(next, position);
+                }
+                self.h_costs.insert(next, next_h);
+                grid.mark_frontier(&[next], None, None);
+                format!(
+                    "push {}: move {} + turn {} → g={}, f={}",
+                    next_heading.name(),
+                    move_cost,
+                    turn_cost,
+                    tentative_g,
+                    tentative_g + next_h
+                )
+            };
+
+            self.last_neighbors.push(NeighborInfo {
+                pos: next,
+                g: Some(tentative_g),
+                h: Some(next_h),
+                f: Some(tentative_g + next_h),
+                decision,
+            });
+        }
 
         StepResult::Continue
     }
 
+    fn reconstruct_heading_path(&self, goal: (Position, Heading)) -> Vec<Position> {
+        let mut path = vec![goal.0];
+        let mut current = goal;
+        while let Some(&parent) = self.heading_came_from.get(&current) {
+            path.push(parent.0);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
     fn step_bfs(&mut self, goal: Position, grid: &mut Grid) -> StepResult {
         if self.bfs_queue.is_empty() {
             self.last_step_info = "Queue empty → no path".to_string();
@@ -221,6 +593,7 @@ impl PathfindingState {
         self.current_node = Some(current);
         self.closed_set.insert(current);
         self.step_count += 1;
+        self.visit_order.entry(current).or_insert(self.step_count);
 
         if let Some(previous_node) = self.previous_node {
             grid.mark_previous_node_as_visited(previous_node);
@@ -279,9 +652,6 @@ impl PathfindingState {
             });
         }
 
-        let visited: Vec<Position> = self.closed_set.iter().copied().collect();
-        grid.mark_visited(&visited, None, None);
-
         StepResult::Continue
     }
 
@@ -295,6 +665,7 @@ impl PathfindingState {
         self.current_node = Some(current);
         self.closed_set.insert(current);
         self.step_count += 1;
+        self.visit_order.entry(current).or_insert(self.step_count);
 
         if let Some(previous_node) = self.previous_node {
             grid.mark_previous_node_as_visited(previous_node);
@@ -353,12 +724,40 @@ impl PathfindingState {
             });
         }
 
-        let visited: Vec<Position> = self.closed_set.iter().copied().collect();
-        grid.mark_visited(&visited, None, None);
-
         StepResult::Continue
     }
 
+    /// Runs `algorithm` to completion on `grid` and returns the path, if any.
+    /// Space-time A* plans around `obstacles`; the others ignore them.
+    pub fn solve(
+        algorithm: &Algorithm,
+        start: Position,
+        goal: Position,
+        obstacles: &[DynamicObstacle],
+        grid: &mut Grid,
+    ) -> Option<Vec<Position>> {
+        let mut state = Self::new();
+        state.initialize(algorithm, grid.topology, start, goal);
+        state.set_dynamic_obstacles(obstacles);
+        state.run(algorithm, goal, grid)
+    }
+
+    /// Steps an initialized search until it finishes.
+    pub fn run(
+        &mut self,
+        algorithm: &Algorithm,
+        goal: Position,
+        grid: &mut Grid,
+    ) -> Option<Vec<Position>> {
+        loop {
+            match self.step(algorithm, goal, grid) {
+                StepResult::Continue => {}
+                StepResult::PathFound(path) => return Some(path),
+                StepResult::NoPath => return None,
+            }
+        }
+    }
+
     fn reconstruct_path(&self, goal: Position) -> Vec<Position> {
         let mut path = Vec::new();
         let mut current = goal;
@@ -377,9 +776,10 @@ impl PathfindingState {
     // Public getters for UI
     pub fn frontier_len(&self, algorithm: &Algorithm) -> usize {
         match algorithm {
-            Algorithm::AStar => self.open_set.len(),
+            Algorithm::AStar | Algorithm::SpaceTimeAStar => self.open_set.len(),
             Algorithm::Bfs => self.bfs_queue.len(),
             Algorithm::Dfs => self.dfs_stack.len(),
+            Algorithm::HeadingAStar => self.heading_open.len(),
         }
     }
 
@@ -407,6 +807,10 @@ impl PathfindingState {
         &self.came_from
     }
 
+    pub fn visit_order(&self, pos: &Position) -> Option<usize> {
+        self.visit_order.get(pos).copied()
+    }
+
     pub fn g_cost(&self, pos: &Position) -> Option<i32> {
         self.g_costs.get(pos).copied()
     }
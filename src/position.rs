@@ -1,24 +1,40 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+use crate::topology::Topology;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
+    // Layer of a multi-floor grid, 0 for the ground floor
+    #[serde(default)]
+    pub floor: i32,
 }
 
 impl Position {
     pub fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+        Self { x, y, floor: 0 }
+    }
+
+    pub fn on_floor(x: i32, y: i32, floor: i32) -> Self {
+        Self { x, y, floor }
     }
 
     pub fn manhattan_distance_to(&self, other: &Position) -> i32 {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
 
-    pub fn neighbors(&self) -> Vec<Position> {
-        vec![
-            Position::new(self.x, self.y - 1),
-            Position::new(self.x + 1, self.y),
-            Position::new(self.x, self.y + 1),
-            Position::new(self.x - 1, self.y),
-        ]
+    /// Steps to `other` on an empty grid: Manhattan on squares, hex
+    /// distance on hexagons. Floors are ignored, so it stays a lower bound
+    /// across stairs and elevators.
+    pub fn distance_to(&self, other: &Position, topology: Topology) -> i32 {
+        topology.distance(self.x - other.x, self.y - other.y)
+    }
+
+    pub fn neighbors(&self, topology: Topology) -> Vec<Position> {
+        topology
+            .offsets()
+            .iter()
+            .map(|(dx, dy)| Position::on_floor(self.x + dx, self.y + dy, self.floor))
+            .collect()
     }
 }
@@ -0,0 +1,158 @@
+use crate::{grid::Grid, position::Position, smoothing::cell_center};
+use egui::Vec2;
+
+/// Beyond this distance the attraction stops growing (conic well).
+const QUADRATIC_RADIUS: f32 = 2.0;
+/// Within this distance of the goal the descent counts as arrived.
+const GOAL_TOLERANCE: f32 = 0.5;
+/// Steps over which the robot must make progress before it is declared
+/// trapped in a local minimum.
+const PROGRESS_WINDOW: usize = 40;
+const MIN_PROGRESS: f32 = 0.3;
+const STEP_LIMIT: usize = 5000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PotentialConfig {
+    pub attractive_gain: f32,
+    pub repulsive_gain: f32,
+    // Obstacles farther than this exert no force, in cells
+    pub influence_radius: f32,
+    pub step_size: f32,
+}
+
+impl Default for PotentialConfig {
+    fn default() -> Self {
+        Self {
+            attractive_gain: 1.0,
+            repulsive_gain: 3.0,
+            influence_radius: 2.5,
+            step_size: 0.2,
+        }
+    }
+}
+
+/// Negative gradient of the combined potential at `point`: a quadratic
+/// well around the goal that turns conic farther out, plus the classic
+/// repulsive term `k (1/d - 1/d0) / d²` from every blocked cell (and the
+/// map border) within the influence radius.
+pub fn force(grid: &Grid, config: &PotentialConfig, goal: Vec2, point: Vec2) -> Vec2 {
+    let to_goal = goal - point;
+    let distance = to_goal.length();
+    let attractive = if distance <= QUADRATIC_RADIUS {
+        to_goal * config.attractive_gain
+    } else {
+        to_goal / distance * QUADRATIC_RADIUS * config.attractive_gain
+    };
+
+    let mut repulsive = Vec2::ZERO;
+    let reach = config.influence_radius.ceil() as i32;
+    let (cx, cy) = (point.x.floor() as i32, point.y.floor() as i32);
+    for y in cy - reach..=cy + reach {
+        for x in cx - reach..=cx + reach {
+            if grid.is_walkable(&Position::new(x, y)) {
+                continue;
+            }
+            // Closest point of the blocked cell's square
+            let nearest = Vec2::new(
+                point.x.clamp(x as f32, x as f32 + 1.0),
+                point.y.clamp(y as f32, y as f32 + 1.0),
+            );
+            let away = point - nearest;
+            let d = away.length().max(0.05);
+            if d < config.influence_radius {
+                let magnitude =
+                    config.repulsive_gain * (1.0 / d - 1.0 / config.influence_radius) / (d * d);
+                repulsive += away / d * magnitude;
+            }
+        }
+    }
+    attractive + repulsive
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DescentStatus {
+    Running,
+    Reached,
+    // Trapped where the forces cancel, at the given point
+    LocalMinimum(Vec2),
+    Collided,
+    StepLimit,
+}
+
+/// Gradient descent on the potential from the start, one step at a time.
+pub struct PotentialRun {
+    pub config: PotentialConfig,
+    pub goal: Vec2,
+    pub trajectory: Vec<Vec2>,
+    pub status: DescentStatus,
+}
+
+impl PotentialRun {
+    pub fn new(start: Position, goal: Position, config: PotentialConfig) -> Self {
+        Self {
+            config,
+            goal: cell_center(&goal),
+            trajectory: vec![cell_center(&start)],
+            status: DescentStatus::Running,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == DescentStatus::Running
+    }
+
+    pub fn position(&self) -> Vec2 {
+        *self.trajectory.last().unwrap()
+    }
+
+    pub fn step(&mut self, grid: &Grid) {
+        if !self.is_running() {
+            return;
+        }
+        let point = self.position();
+        if (self.goal - point).length() < GOAL_TOLERANCE {
+            self.status = DescentStatus::Reached;
+            return;
+        }
+        let force = force(grid, &self.config, self.goal, point);
+        if force.length() < 1e-4 {
+            self.status = DescentStatus::LocalMinimum(point);
+            return;
+        }
+
+        let next = point + force.normalized() * self.config.step_size;
+        if !grid.is_walkable_point(next) {
+            self.status = DescentStatus::Collided;
+            return;
+        }
+        self.trajectory.push(next);
+
+        // Oscillating in place around a point where the forces balance
+        if self.trajectory.len() > PROGRESS_WINDOW {
+            let window = &self.trajectory[self.trajectory.len() - PROGRESS_WINDOW..];
+            let anchor = window[0];
+            if window.iter().all(|p| (*p - anchor).length() < MIN_PROGRESS) {
+                let center =
+                    window.iter().fold(Vec2::ZERO, |sum, p| sum + *p) / PROGRESS_WINDOW as f32;
+                self.status = DescentStatus::LocalMinimum(center);
+                return;
+            }
+        }
+        if self.trajectory.len() > STEP_LIMIT {
+            self.status = DescentStatus::StepLimit;
+        }
+    }
+
+    pub fn finish(&mut self, grid: &Grid) {
+        while self.is_running() {
+            self.step(grid);
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.trajectory
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).length())
+            .sum()
+    }
+}
@@ -0,0 +1,453 @@
+use crate::hybrid_astar::Pose;
+use std::f32::consts::{PI, TAU};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Steer {
+    Left,
+    Straight,
+    Right,
+}
+
+impl Steer {
+    fn curvature(self) -> f32 {
+        match self {
+            Self::Left => 1.0,
+            Self::Straight => 0.0,
+            Self::Right => -1.0,
+        }
+    }
+}
+
+/// One piece of a Reeds-Shepp path. `length` is measured in turning radii
+/// (radians for arcs) and is negative when driving in reverse.
+#[derive(Clone, Copy, Debug)]
+pub struct Segment {
+    pub steer: Steer,
+    pub length: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReedsSheppPath {
+    pub segments: Vec<Segment>,
+    pub radius: f32,
+}
+
+impl ReedsSheppPath {
+    /// Poses every `step` cells along the path from `start`, each with a
+    /// flag that is true while reversing. The final pose is always included.
+    pub fn sample(&self, start: Pose, step: f32) -> Vec<(Pose, bool)> {
+        let mut samples = vec![(start, self.segments.first().is_some_and(|s| s.length < 0.0))];
+        let mut pose = start;
+        for segment in &self.segments {
+            let distance = segment.length * self.radius;
+            let count = (distance.abs() / step).ceil().max(1.0) as usize;
+            for i in 1..=count {
+                let travelled = distance * i as f32 / count as f32;
+                let sample = advance(pose, segment.steer.curvature() / self.radius, travelled);
+                samples.push((sample, segment.length < 0.0));
+            }
+            pose = advance(pose, segment.steer.curvature() / self.radius, distance);
+        }
+        samples
+    }
+}
+
+/// Drives `distance` cells (negative reverses) along a constant-curvature
+/// arc starting at `pose`.
+pub fn advance(pose: Pose, curvature: f32, distance: f32) -> Pose {
+    if curvature == 0.0 {
+        return Pose {
+            x: pose.x + distance * pose.theta.cos(),
+            y: pose.y + distance * pose.theta.sin(),
+            theta: pose.theta,
+        };
+    }
+    let theta = pose.theta + curvature * distance;
+    Pose {
+        x: pose.x + (theta.sin() - pose.theta.sin()) / curvature,
+        y: pose.y - (theta.cos() - pose.theta.cos()) / curvature,
+        theta: normalize_angle(theta),
+    }
+}
+
+/// Wraps an angle to (-π, π].
+pub fn normalize_angle(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(TAU);
+    if wrapped > PI { wrapped - TAU } else { wrapped }
+}
+
+/// Shortest Reeds-Shepp path between two poses: the best of the CSC, CCC,
+/// CCCC, CCSC and CCSCC families with their time-flipped, reflected and
+/// backwards variants.
+pub fn shortest_path(start: Pose, goal: Pose, radius: f32) -> Option<ReedsSheppPath> {
+    // Goal expressed in the start frame, scaled to a unit turning radius
+    let (dx, dy) = (goal.x - start.x, goal.y - start.y);
+    let (sin, cos) = start.theta.sin_cos();
+    let x = (dx * cos + dy * sin) / radius;
+    let y = (-dx * sin + dy * cos) / radius;
+    let phi = normalize_angle(goal.theta - start.theta);
+
+    candidates(x, y, phi)
+        .into_iter()
+        .min_by(|a, b| total(a).total_cmp(&total(b)))
+        .map(|segments| ReedsSheppPath { segments, radius })
+}
+
+/// Every word that reaches `(x, y, phi)` from the origin at unit radius.
+fn candidates(x: f32, y: f32, phi: f32) -> Vec<Vec<Segment>> {
+    let mut candidates = Vec::new();
+    csc(x, y, phi, &mut candidates);
+    ccc(x, y, phi, &mut candidates);
+    cccc(x, y, phi, &mut candidates);
+    ccsc(x, y, phi, &mut candidates);
+    ccscc(x, y, phi, &mut candidates);
+    candidates
+}
+
+fn total(segments: &[Segment]) -> f32 {
+    segments.iter().map(|s| s.length.abs()).sum()
+}
+
+fn mod2pi(angle: f32) -> f32 {
+    angle.rem_euclid(TAU)
+}
+
+fn polar(x: f32, y: f32) -> (f32, f32) {
+    ((x * x + y * y).sqrt(), y.atan2(x))
+}
+
+/// Slack on the sign checks of the four- and five-segment formulas.
+const EPSILON: f32 = 1e-4;
+
+fn left_straight_left(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (u, t) = polar(x - phi.sin(), y - 1.0 + phi.cos());
+    let t = mod2pi(t);
+    let v = mod2pi(phi - t);
+    Some(vec![t, u, v])
+}
+
+fn left_straight_right(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (u1, t1) = polar(x + phi.sin(), y - 1.0 - phi.cos());
+    let u1 = u1 * u1;
+    if u1 < 4.0 {
+        return None;
+    }
+    let u = (u1 - 4.0).sqrt();
+    let t = mod2pi(t1 + 2.0f32.atan2(u));
+    let v = mod2pi(t - phi);
+    Some(vec![t, u, v])
+}
+
+fn left_right_left(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (u1, theta) = polar(x - phi.sin(), y - 1.0 + phi.cos());
+    if u1 > 4.0 {
+        return None;
+    }
+    let u = -2.0 * (u1 / 4.0).asin();
+    let t = mod2pi(theta + 0.5 * u + PI);
+    let v = mod2pi(phi - t + u);
+    Some(vec![t, u, v])
+}
+
+/// First and last arc of the CCCC words, from the middle arcs `u` and `v`
+/// and the goal's `(xi, eta)` offset.
+fn tau_omega(u: f32, v: f32, xi: f32, eta: f32, phi: f32) -> (f32, f32) {
+    let delta = normalize_angle(u - v);
+    let a = u.sin() - delta.sin();
+    let b = u.cos() - delta.cos() - 1.0;
+    let t1 = (eta * a - xi * b).atan2(xi * a + eta * b);
+    let t2 = 2.0 * (delta.cos() - v.cos() - u.cos()) + 3.0;
+    let tau = if t2 < 0.0 {
+        normalize_angle(t1 + PI)
+    } else {
+        normalize_angle(t1)
+    };
+    (tau, normalize_angle(tau - u + v - phi))
+}
+
+/// L+ R+ L- R-: the middle arcs have equal length and opposite directions.
+fn left_right_left_right_cusp(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (xi, eta) = (x + phi.sin(), y - 1.0 - phi.cos());
+    let rho = 0.25 * (2.0 + (xi * xi + eta * eta).sqrt());
+    if rho > 1.0 {
+        return None;
+    }
+    let u = rho.acos();
+    let (t, v) = tau_omega(u, -u, xi, eta, phi);
+    (t >= -EPSILON && v <= EPSILON).then(|| vec![t, u, -u, v])
+}
+
+/// L+ R- L- R+: both middle arcs are driven in reverse.
+fn left_right_left_right_reverse(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (xi, eta) = (x + phi.sin(), y - 1.0 - phi.cos());
+    let rho = (20.0 - xi * xi - eta * eta) / 16.0;
+    if !(0.0..=1.0).contains(&rho) {
+        return None;
+    }
+    let u = -rho.acos();
+    if u < -0.5 * PI {
+        return None;
+    }
+    let (t, v) = tau_omega(u, u, xi, eta, phi);
+    (t >= -EPSILON && v >= -EPSILON).then(|| vec![t, u, u, v])
+}
+
+/// L+ R-(π/2) S- L-
+fn left_right_straight_left(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (rho, theta) = polar(x - phi.sin(), y - 1.0 + phi.cos());
+    if rho < 2.0 {
+        return None;
+    }
+    let r = (rho * rho - 4.0).sqrt();
+    let u = 2.0 - r;
+    let t = normalize_angle(theta + r.atan2(-2.0));
+    let v = normalize_angle(phi - 0.5 * PI - t);
+    (t >= -EPSILON && u <= EPSILON && v <= EPSILON).then(|| vec![t, -0.5 * PI, u, v])
+}
+
+/// L+ R-(π/2) S- R-
+fn left_right_straight_right(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (rho, theta) = polar(-(y - 1.0 - phi.cos()), x + phi.sin());
+    if rho < 2.0 {
+        return None;
+    }
+    let t = theta;
+    let u = 2.0 - rho;
+    let v = normalize_angle(t + 0.5 * PI - phi);
+    (t >= -EPSILON && u <= EPSILON && v <= EPSILON).then(|| vec![t, -0.5 * PI, u, v])
+}
+
+/// L+ R-(π/2) S- L-(π/2) R+
+fn left_right_straight_left_right(x: f32, y: f32, phi: f32) -> Option<Vec<f32>> {
+    let (xi, eta) = (x + phi.sin(), y - 1.0 - phi.cos());
+    let (rho, _) = polar(xi, eta);
+    if rho < 2.0 {
+        return None;
+    }
+    let u = 4.0 - (rho * rho - 4.0).sqrt();
+    if u > EPSILON {
+        return None;
+    }
+    let t = normalize_angle(((4.0 - u) * xi - 2.0 * eta).atan2(-2.0 * xi + (u - 4.0) * eta));
+    let v = normalize_angle(t - phi);
+    (t >= -EPSILON && v >= -EPSILON).then(|| vec![t, -0.5 * PI, u, -0.5 * PI, v])
+}
+
+type Formula = fn(f32, f32, f32) -> Option<Vec<f32>>;
+
+/// Adds the four symmetric variants of a base formula: as is, time-flipped
+/// (driven in reverse), reflected (left and right swapped) and both. With
+/// `backwards` the formula is solved from the goal end and the word is
+/// read in reverse.
+fn add_variants(
+    formula: Formula,
+    steers: &[Steer],
+    (x, y, phi): (f32, f32, f32),
+    backwards: bool,
+    out: &mut Vec<Vec<Segment>>,
+) {
+    let (x, y) = if backwards {
+        (x * phi.cos() + y * phi.sin(), x * phi.sin() - y * phi.cos())
+    } else {
+        (x, y)
+    };
+    let variants = [
+        (x, y, phi, false, false),
+        (-x, y, -phi, true, false),
+        (x, -y, -phi, false, true),
+        (-x, -y, phi, true, true),
+    ];
+    for (vx, vy, vphi, flip, mirror) in variants {
+        let Some(lengths) = formula(vx, vy, vphi) else {
+            continue;
+        };
+        let mut word: Vec<Segment> = steers
+            .iter()
+            .zip(lengths)
+            .map(|(&steer, length)| Segment {
+                steer: match steer {
+                    Steer::Left if mirror => Steer::Right,
+                    Steer::Right if mirror => Steer::Left,
+                    steer => steer,
+                },
+                length: if flip { -length } else { length },
+            })
+            .collect();
+        if backwards {
+            word.reverse();
+        }
+        out.push(word);
+    }
+}
+
+fn csc(x: f32, y: f32, phi: f32, out: &mut Vec<Vec<Segment>>) {
+    use Steer::{Left, Right, Straight};
+    add_variants(
+        left_straight_left,
+        &[Left, Straight, Left],
+        (x, y, phi),
+        false,
+        out,
+    );
+    add_variants(
+        left_straight_right,
+        &[Left, Straight, Right],
+        (x, y, phi),
+        false,
+        out,
+    );
+}
+
+fn ccc(x: f32, y: f32, phi: f32, out: &mut Vec<Vec<Segment>>) {
+    use Steer::{Left, Right};
+    add_variants(
+        left_right_left,
+        &[Left, Right, Left],
+        (x, y, phi),
+        false,
+        out,
+    );
+    add_variants(
+        left_right_left,
+        &[Left, Right, Left],
+        (x, y, phi),
+        true,
+        out,
+    );
+}
+
+fn cccc(x: f32, y: f32, phi: f32, out: &mut Vec<Vec<Segment>>) {
+    use Steer::{Left, Right};
+    let steers = [Left, Right, Left, Right];
+    add_variants(left_right_left_right_cusp, &steers, (x, y, phi), false, out);
+    add_variants(
+        left_right_left_right_reverse,
+        &steers,
+        (x, y, phi),
+        false,
+        out,
+    );
+}
+
+fn ccsc(x: f32, y: f32, phi: f32, out: &mut Vec<Vec<Segment>>) {
+    use Steer::{Left, Right, Straight};
+    for backwards in [false, true] {
+        add_variants(
+            left_right_straight_left,
+            &[Left, Right, Straight, Left],
+            (x, y, phi),
+            backwards,
+            out,
+        );
+        add_variants(
+            left_right_straight_right,
+            &[Left, Right, Straight, Right],
+            (x, y, phi),
+            backwards,
+            out,
+        );
+    }
+}
+
+fn ccscc(x: f32, y: f32, phi: f32, out: &mut Vec<Vec<Segment>>) {
+    use Steer::{Left, Right, Straight};
+    add_variants(
+        left_right_straight_left_right,
+        &[Left, Right, Straight, Left, Right],
+        (x, y, phi),
+        false,
+        out,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_reaches(start: Pose, goal: Pose, radius: f32) {
+        let path = shortest_path(start, goal, radius).expect("a path");
+        let (end, _) = *path.sample(start, 0.25).last().unwrap();
+        assert!(
+            (end.x - goal.x).abs() < 1e-3 && (end.y - goal.y).abs() < 1e-3,
+            "{start:?} -> {goal:?} ended at {end:?}"
+        );
+        assert!(normalize_angle(end.theta - goal.theta).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sampled_path_ends_at_the_goal_pose() {
+        let start = Pose {
+            x: 2.0,
+            y: 3.0,
+            theta: 0.3,
+        };
+        for (x, y) in [
+            (10.0, 3.0),
+            (2.5, 3.5),
+            (-4.0, 6.0),
+            (2.0, -5.0),
+            (3.0, 3.0),
+        ] {
+            for theta in [0.0, PI / 2.0, PI, -2.5, 1.0] {
+                assert_reaches(start, Pose { x, y, theta }, 2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn every_family_word_reaches_its_goal() {
+        let origin = Pose {
+            x: 0.0,
+            y: 0.0,
+            theta: 0.0,
+        };
+        let mut families = std::collections::HashSet::new();
+        for xi in -6..=6 {
+            for yi in -6..=6 {
+                for pi in -4..4 {
+                    let (x, y, phi) = (xi as f32 * 0.7, yi as f32 * 0.7, pi as f32 * PI / 4.0);
+                    for segments in candidates(x, y, phi) {
+                        let family: String = segments
+                            .iter()
+                            .map(|s| if s.steer == Steer::Straight { 'S' } else { 'C' })
+                            .collect();
+                        families.insert(family);
+                        let path = ReedsSheppPath {
+                            segments,
+                            radius: 1.0,
+                        };
+                        let (end, _) = *path.sample(origin, 0.25).last().unwrap();
+                        assert!(
+                            (end.x - x).abs() < 1e-2
+                                && (end.y - y).abs() < 1e-2
+                                && normalize_angle(end.theta - phi).abs() < 1e-2,
+                            "{:?} to ({x}, {y}, {phi}) ended at {end:?}",
+                            path.segments
+                        );
+                    }
+                }
+            }
+        }
+        // CSCC is the backwards CCSC
+        for family in ["CSC", "CCC", "CCCC", "CCSC", "CSCC", "CCSCC"] {
+            assert!(families.contains(family), "no {family} word");
+        }
+    }
+
+    #[test]
+    fn parallel_park_reverses() {
+        let start = Pose {
+            x: 0.0,
+            y: 0.0,
+            theta: 0.0,
+        };
+        let goal = Pose {
+            x: 0.0,
+            y: 2.0,
+            theta: 0.0,
+        };
+        assert_reaches(start, goal, 3.0);
+        let path = shortest_path(start, goal, 3.0).unwrap();
+        assert!(path.segments.iter().any(|s| s.length < 0.0));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::position::Position;
+use egui::Vec2;
+use std::f32::consts::{PI, TAU};
+
+/// A robot that drives along a found path with interpolated motion.
+pub struct Robot {
+    path: Vec<Position>,
+    // Distance travelled along the path, in cells
+    progress: f32,
+    pub speed: f32,
+    pub playing: bool,
+}
+
+impl Default for Robot {
+    fn default() -> Self {
+        Self {
+            path: Vec::new(),
+            progress: 0.0,
+            speed: 4.0,
+            playing: false,
+        }
+    }
+}
+
+impl Robot {
+    pub fn set_path(&mut self, path: Vec<Position>) {
+        self.path = path;
+        self.progress = 0.0;
+        self.playing = !self.path.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.path.clear();
+        self.progress = 0.0;
+        self.playing = false;
+    }
+
+    pub fn reset(&mut self) {
+        self.progress = 0.0;
+        self.playing = false;
+    }
+
+    /// Distance travelled along the path, which is also the time in steps.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    pub fn has_path(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress >= self.path_length()
+    }
+
+    fn path_length(&self) -> f32 {
+        self.path.len().saturating_sub(1) as f32
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.progress = (self.progress + self.speed * dt).min(self.path_length());
+        if self.is_finished() {
+            self.playing = false;
+        }
+    }
+
+    /// Floor of the cell the robot is closest to along its path.
+    pub fn floor(&self) -> Option<i32> {
+        let index = (self.progress.round() as usize).min(self.path.len().checked_sub(1)?);
+        Some(self.path[index].floor)
+    }
+
+    /// Current position (in grid units, at the cell centre) and heading in radians.
+    pub fn pose(&self) -> Option<(Vec2, f32)> {
+        let first = *self.path.first()?;
+        if self.path.len() == 1 {
+            return Some((center(first), 0.0));
+        }
+
+        let segment = (self.progress.floor() as usize).min(self.path.len() - 2);
+        let t = self.progress - segment as f32;
+        let from = center(self.path[segment]);
+        let to = center(self.path[segment + 1]);
+        let position = from + (to - from) * t;
+
+        // Start turning towards the next segment over the last part of this one
+        let heading = self.heading_at(segment);
+        let heading = match self.path.get(segment + 2) {
+            Some(_) if t > 0.7 => {
+                let next_heading = self.heading_at(segment + 1);
+                lerp_angle(heading, next_heading, (t - 0.7) / 0.3 * 0.5)
+            }
+            _ if segment > 0 && t < 0.3 => {
+                let previous_heading = self.heading_at(segment - 1);
+                lerp_angle(previous_heading, heading, 0.5 + t / 0.3 * 0.5)
+            }
+            _ => heading,
+        };
+
+        Some((position, heading))
+    }
+
+    /// Heading of the last segment up to `segment` that actually moves, so
+    /// the robot keeps facing the same way while it waits in place or
+    /// changes floors.
+    fn heading_at(&self, segment: usize) -> f32 {
+        (0..=segment)
+            .rev()
+            .map(|i| (self.path[i], self.path[i + 1]))
+            .find(|(from, to)| (from.x, from.y) != (to.x, to.y))
+            .map_or(0.0, |(from, to)| segment_heading(center(from), center(to)))
+    }
+}
+
+fn center(pos: Position) -> Vec2 {
+    Vec2::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5)
+}
+
+fn segment_heading(from: Vec2, to: Vec2) -> f32 {
+    let delta = to - from;
+    delta.y.atan2(delta.x)
+}
+
+fn lerp_angle(a: f32, b: f32, t: f32) -> f32 {
+    let mut delta = (b - a) % TAU;
+    if delta > PI {
+        delta -= TAU;
+    } else if delta < -PI {
+        delta += TAU;
+    }
+    a + delta * t
+}
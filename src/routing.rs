@@ -0,0 +1,377 @@
+use crate::{grid::Grid, position::Position};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Waypoint counts up to this are ordered exactly with Held-Karp.
+pub const EXACT_LIMIT: usize = 10;
+
+pub struct Leg {
+    pub from: Position,
+    pub to: Position,
+    pub path: Vec<Position>,
+    cost: usize,
+}
+
+impl Leg {
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+}
+
+pub struct Tour {
+    pub legs: Vec<Leg>,
+    // Waypoint indices in visiting order
+    pub order: Vec<usize>,
+    pub exact: bool,
+}
+
+impl Tour {
+    pub fn cost(&self) -> usize {
+        self.legs.iter().map(Leg::cost).sum()
+    }
+
+    /// The whole tour as one continuous path.
+    pub fn path(&self) -> Vec<Position> {
+        let mut path = Vec::new();
+        for leg in &self.legs {
+            let skip = usize::from(!path.is_empty());
+            path.extend(leg.path.iter().skip(skip));
+        }
+        path
+    }
+}
+
+pub enum RoutingError {
+    Unreachable(Position, Position),
+}
+
+/// Plans a route from `start` through every waypoint, optionally finishing
+/// at `end`. Pairwise legs are shortest paths; when `ordered` is false the
+/// visiting order is optimised as well.
+pub fn plan_tour(
+    grid: &Grid,
+    start: Position,
+    waypoints: &[Position],
+    end: Option<Position>,
+    ordered: bool,
+) -> Result<Tour, RoutingError> {
+    // Node 0 is the start, 1..=n the waypoints, n+1 the optional end
+    let mut nodes = vec![start];
+    nodes.extend_from_slice(waypoints);
+    nodes.extend(end);
+
+    // One search per node fills its whole row of the cost matrix
+    let searches: Vec<ShortestPaths> = nodes
+        .iter()
+        .map(|&node| ShortestPaths::from(grid, node))
+        .collect();
+    let distance = |i: usize, j: usize| -> Option<usize> {
+        if i == j {
+            Some(0)
+        } else {
+            searches[i].cost(&nodes[j])
+        }
+    };
+
+    let waypoint_count = waypoints.len();
+    let end_node = end.map(|_| waypoint_count + 1);
+    let (order, exact) = if ordered {
+        ((0..waypoint_count).collect(), true)
+    } else if waypoint_count <= EXACT_LIMIT {
+        (held_karp(waypoint_count, end_node, &distance), true)
+    } else {
+        (
+            nearest_neighbor_two_opt(waypoint_count, end_node, &distance),
+            false,
+        )
+    };
+
+    let mut sequence: Vec<usize> = std::iter::once(0)
+        .chain(order.iter().map(|w| w + 1))
+        .collect();
+    sequence.extend(end_node);
+
+    let mut legs = Vec::new();
+    for pair in sequence.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let (path, cost) = searches[from]
+            .path_to(nodes[to])
+            .zip(distance(from, to))
+            .ok_or(RoutingError::Unreachable(nodes[from], nodes[to]))?;
+        legs.push(Leg {
+            from: nodes[from],
+            to: nodes[to],
+            path,
+            cost,
+        });
+    }
+
+    Ok(Tour { legs, order, exact })
+}
+
+/// Single-source Dijkstra over the grid's walkable neighbours, using the
+/// same move costs as A*. The planners in `PathfindingState` stop at one
+/// goal, so filling the cost matrix with them would take a search per pair
+/// of nodes; one Dijkstra run per node settles every other node at once and
+/// keeps Held-Karp tours interactive.
+struct ShortestPaths {
+    cost: HashMap<Position, usize>,
+    came_from: HashMap<Position, Position>,
+}
+
+impl ShortestPaths {
+    fn from(grid: &Grid, source: Position) -> Self {
+        let mut cost = HashMap::from([(source, 0)]);
+        let mut came_from = HashMap::new();
+        let mut open = BinaryHeap::from([Reverse((0, source.x, source.y, source.floor))]);
+        while let Some(Reverse((d, x, y, floor))) = open.pop() {
+            let pos = Position::on_floor(x, y, floor);
+            if d > cost[&pos] {
+                continue;
+            }
+            for next in grid.get_walkable_neighbors(&pos) {
+                let next_cost = d + grid.move_cost(&pos, &next) as usize;
+                if cost.get(&next).is_none_or(|&c| next_cost < c) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, pos);
+                    open.push(Reverse((next_cost, next.x, next.y, next.floor)));
+                }
+            }
+        }
+        Self { cost, came_from }
+    }
+
+    fn cost(&self, target: &Position) -> Option<usize> {
+        self.cost.get(target).copied()
+    }
+
+    fn path_to(&self, target: Position) -> Option<Vec<Position>> {
+        self.cost.get(&target)?;
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(&previous) = self.came_from.get(&current) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Unreachable pairs get a large finite cost so the solvers still return an
+/// order; `plan_tour` reports them afterwards.
+const UNREACHABLE: usize = usize::MAX / 4;
+
+/// Exact open-path TSP over waypoint nodes `1..=n` starting at node 0.
+fn held_karp(
+    n: usize,
+    end: Option<usize>,
+    distance: &impl Fn(usize, usize) -> Option<usize>,
+) -> Vec<usize> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let d = |i, j| distance(i, j).unwrap_or(UNREACHABLE);
+    let full = (1 << n) - 1;
+    let mut cost = vec![vec![usize::MAX; n]; 1 << n];
+    let mut parent = vec![vec![usize::MAX; n]; 1 << n];
+    for j in 0..n {
+        cost[1 << j][j] = d(0, j + 1);
+    }
+    for mask in 1..=full {
+        for last in 0..n {
+            if mask & (1 << last) == 0 || cost[mask][last] == usize::MAX {
+                continue;
+            }
+            for next in 0..n {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let next_mask = mask | (1 << next);
+                let candidate = cost[mask][last].saturating_add(d(last + 1, next + 1));
+                if candidate < cost[next_mask][next] {
+                    cost[next_mask][next] = candidate;
+                    parent[next_mask][next] = last;
+                }
+            }
+        }
+    }
+
+    let finish = |j: usize| {
+        let tail = end.map_or(0, |e| d(j + 1, e));
+        cost[full][j].saturating_add(tail)
+    };
+    let mut last = (0..n).min_by_key(|&j| finish(j)).unwrap();
+    let mut mask = full;
+    let mut order = Vec::with_capacity(n);
+    loop {
+        order.push(last);
+        let previous = parent[mask][last];
+        mask &= !(1 << last);
+        if previous == usize::MAX {
+            break;
+        }
+        last = previous;
+    }
+    order.reverse();
+    order
+}
+
+/// Nearest-neighbour construction followed by 2-opt improvement.
+fn nearest_neighbor_two_opt(
+    n: usize,
+    end: Option<usize>,
+    distance: &impl Fn(usize, usize) -> Option<usize>,
+) -> Vec<usize> {
+    let tour = two_opt(nearest_neighbor(n, end, distance), end, distance);
+    tour.into_iter()
+        .filter(|&node| node != 0 && Some(node) != end)
+        .map(|node| node - 1)
+        .collect()
+}
+
+/// Greedy route over nodes `0..=n` (plus `end`), always moving to the
+/// closest unvisited waypoint.
+fn nearest_neighbor(
+    n: usize,
+    end: Option<usize>,
+    distance: &impl Fn(usize, usize) -> Option<usize>,
+) -> Vec<usize> {
+    let d = |i, j| distance(i, j).unwrap_or(UNREACHABLE);
+    let mut remaining: Vec<usize> = (1..=n).collect();
+    let mut tour = vec![0];
+    while !remaining.is_empty() {
+        let last = *tour.last().unwrap();
+        let (index, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, node)| d(last, **node))
+            .unwrap();
+        tour.push(remaining.swap_remove(index));
+    }
+    tour.extend(end);
+    tour
+}
+
+/// Improves a route by reversing segments while that shortens it.
+fn two_opt(
+    mut tour: Vec<usize>,
+    end: Option<usize>,
+    distance: &impl Fn(usize, usize) -> Option<usize>,
+) -> Vec<usize> {
+    let d = |i, j| distance(i, j).unwrap_or(UNREACHABLE);
+
+    // Reverse tour[i..=k] while that shortens the route; endpoints stay fixed
+    let last_movable = tour.len() - 1 - usize::from(end.is_some());
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..last_movable {
+            for k in i + 1..=last_movable {
+                let before =
+                    d(tour[i - 1], tour[i]) + tour.get(k + 1).map_or(0, |&next| d(tour[k], next));
+                let after =
+                    d(tour[i - 1], tour[k]) + tour.get(k + 1).map_or(0, |&next| d(tour[i], next));
+                if after < before {
+                    tour[i..=k].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Distances between pseudo-random points, rounded to whole units.
+    fn random_distances(count: usize, seed: u64) -> Vec<Vec<usize>> {
+        let mut state = seed;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as f32 / (1u64 << 31) as f32 * 100.0
+        };
+        let points: Vec<(f32, f32)> = (0..count).map(|_| (next(), next())).collect();
+        points
+            .iter()
+            .map(|a| {
+                points
+                    .iter()
+                    .map(|b| ((a.0 - b.0).hypot(a.1 - b.1)).round() as usize)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Cost of visiting waypoints `order` (0-based) from node 0, then `end`.
+    fn order_cost(matrix: &[Vec<usize>], order: &[usize], end: Option<usize>) -> usize {
+        let route: Vec<usize> = std::iter::once(0)
+            .chain(order.iter().map(|w| w + 1))
+            .chain(end)
+            .collect();
+        route_cost(matrix, &route)
+    }
+
+    fn route_cost(matrix: &[Vec<usize>], route: &[usize]) -> usize {
+        route.windows(2).map(|pair| matrix[pair[0]][pair[1]]).sum()
+    }
+
+    fn permutations(items: Vec<usize>) -> Vec<Vec<usize>> {
+        if items.len() <= 1 {
+            return vec![items];
+        }
+        let mut result = Vec::new();
+        for i in 0..items.len() {
+            let mut rest = items.clone();
+            let first = rest.remove(i);
+            for mut tail in permutations(rest) {
+                tail.insert(0, first);
+                result.push(tail);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn held_karp_matches_brute_force() {
+        for seed in 0..20 {
+            for (n, end) in [(5, None), (5, Some(6)), (6, None), (1, Some(2))] {
+                let matrix = random_distances(n + 2, seed);
+                let distance = |i: usize, j: usize| Some(matrix[i][j]);
+                let best = permutations((0..n).collect())
+                    .iter()
+                    .map(|order| order_cost(&matrix, order, end))
+                    .min()
+                    .unwrap();
+                let order = held_karp(n, end, &distance);
+                assert_eq!(order_cost(&matrix, &order, end), best, "seed {seed}, n {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn two_opt_never_lengthens_a_route() {
+        for seed in 0..20 {
+            for end in [None, Some(13)] {
+                let matrix = random_distances(14, seed);
+                let distance = |i: usize, j: usize| Some(matrix[i][j]);
+                let greedy = nearest_neighbor(12, end, &distance);
+                let improved = two_opt(greedy.clone(), end, &distance);
+                assert!(route_cost(&matrix, &improved) <= route_cost(&matrix, &greedy));
+
+                let mut listed: Vec<usize> = (0..=12).collect();
+                listed.extend(end);
+                let improved = two_opt(listed.clone(), end, &distance);
+                assert!(route_cost(&matrix, &improved) <= route_cost(&matrix, &listed));
+                assert_eq!(improved.first(), Some(&0));
+                if let Some(end) = end {
+                    assert_eq!(improved.last(), Some(&end));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{
+    grid::Grid,
+    position::Position,
+    smoothing::{cell_center, segment_is_clear},
+};
+use egui::Vec2;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SamplingPlanner {
+    Rrt,
+    RrtStar,
+    Prm,
+}
+
+impl SamplingPlanner {
+    pub const ALL: [SamplingPlanner; 3] = [Self::Rrt, Self::RrtStar, Self::Prm];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rrt => "RRT",
+            Self::RrtStar => "RRT*",
+            Self::Prm => "PRM",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Rrt => {
+                "Rapidly-exploring Random Tree grows a tree from the start by sampling random points and extending the nearest node a fixed step towards each one. It stops at the first connection to the goal, so paths are feasible but jagged."
+            }
+            Self::RrtStar => {
+                "RRT* picks the cheapest parent among nearby nodes for every new node and rewires neighbours through it when that shortens their path. It keeps sampling after reaching the goal, so the path converges towards the shortest one."
+            }
+            Self::Prm => {
+                "Probabilistic Roadmap scatters random collision-free samples and links every pair within the connection radius that can see each other. The roadmap is built once; each query only connects the start and goal to it and runs Dijkstra."
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SamplingConfig {
+    pub seed: u64,
+    // Maximum distance a tree extension travels, in cells
+    pub step_size: f32,
+    // Probability of sampling the goal instead of a random point
+    pub goal_bias: f32,
+    pub rewire_radius: f32,
+    pub max_iterations: usize,
+    pub roadmap_samples: usize,
+    pub connection_radius: f32,
+    // Samples drawn per solver step
+    pub batch: usize,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            step_size: 2.0,
+            goal_bias: 0.05,
+            rewire_radius: 4.0,
+            max_iterations: 2000,
+            roadmap_samples: 200,
+            connection_radius: 5.0,
+            batch: 5,
+        }
+    }
+}
+
+/// Small deterministic generator (SplitMix64) so runs replay from a seed.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn point(&mut self, grid: &Grid) -> Vec2 {
+        Vec2::new(
+            self.next_f32() * grid.width as f32,
+            self.next_f32() * grid.height as f32,
+        )
+    }
+}
+
+/// An RRT or RRT* search, grown a batch of samples per step.
+pub struct TreeRun {
+    pub star: bool,
+    config: SamplingConfig,
+    rng: Rng,
+    pub nodes: Vec<Vec2>,
+    pub parents: Vec<Option<usize>>,
+    costs: Vec<f32>,
+    goal: Vec2,
+    // Nodes with a collision-free edge to the goal
+    goal_links: Vec<usize>,
+    pub path: Vec<Vec2>,
+    pub iterations: usize,
+    pub last_sample: Option<Vec2>,
+}
+
+impl TreeRun {
+    pub fn new(start: Position, goal: Position, star: bool, config: SamplingConfig) -> Self {
+        Self {
+            star,
+            config,
+            rng: Rng(config.seed),
+            nodes: vec![cell_center(&start)],
+            parents: vec![None],
+            costs: vec![0.0],
+            goal: cell_center(&goal),
+            goal_links: Vec::new(),
+            path: Vec::new(),
+            iterations: 0,
+            last_sample: None,
+        }
+    }
+
+    /// RRT stops at the first solution; RRT* keeps refining until the
+    /// iteration budget runs out.
+    pub fn is_running(&self) -> bool {
+        self.iterations < self.config.max_iterations && (self.star || self.path.is_empty())
+    }
+
+    pub fn path_length(&self) -> Option<f32> {
+        self.best_goal_link().map(|(_, cost)| cost)
+    }
+
+    pub fn step(&mut self, grid: &Grid) {
+        for _ in 0..self.config.batch.max(1) {
+            if !self.is_running() {
+                return;
+            }
+            self.iterations += 1;
+            self.extend(grid);
+        }
+        self.update_path();
+    }
+
+    pub fn finish(&mut self, grid: &Grid) {
+        while self.is_running() {
+            self.step(grid);
+        }
+    }
+
+    fn extend(&mut self, grid: &Grid) {
+        let sample = if self.rng.next_f32() < self.config.goal_bias {
+            self.goal
+        } else {
+            self.rng.point(grid)
+        };
+        self.last_sample = Some(sample);
+
+        let nearest = nearest(&self.nodes, sample);
+        let from = self.nodes[nearest];
+        let offset = sample - from;
+        let new = if offset.length() > self.config.step_size {
+            from + offset.normalized() * self.config.step_size
+        } else {
+            sample
+        };
+        if offset.length() < 1e-3 || !segment_is_clear(grid, from, new) {
+            return;
+        }
+
+        let mut parent = nearest;
+        let mut cost = self.costs[nearest] + (new - from).length();
+        let near: Vec<usize> = if self.star {
+            (0..self.nodes.len())
+                .filter(|&i| (self.nodes[i] - new).length() <= self.config.rewire_radius)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // Cheapest collision-free parent in the neighbourhood
+        for &i in &near {
+            let candidate = self.costs[i] + (new - self.nodes[i]).length();
+            if candidate < cost && segment_is_clear(grid, self.nodes[i], new) {
+                parent = i;
+                cost = candidate;
+            }
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(new);
+        self.parents.push(Some(parent));
+        self.costs.push(cost);
+
+        // Rewire neighbours that become cheaper through the new node
+        for &i in &near {
+            let through = cost + (self.nodes[i] - new).length();
+            if through + 1e-4 < self.costs[i] && segment_is_clear(grid, new, self.nodes[i]) {
+                self.parents[i] = Some(index);
+                self.propagate_cost(i, through);
+            }
+        }
+
+        let to_goal = (self.goal - new).length();
+        if to_goal <= self.config.step_size && segment_is_clear(grid, new, self.goal) {
+            self.goal_links.push(index);
+        }
+    }
+
+    /// Sets a node's cost and shifts its whole subtree by the same amount.
+    fn propagate_cost(&mut self, node: usize, cost: f32) {
+        let mut stack = vec![(node, cost - self.costs[node])];
+        while let Some((current, delta)) = stack.pop() {
+            self.costs[current] += delta;
+            for (child, parent) in self.parents.iter().enumerate() {
+                if *parent == Some(current) {
+                    stack.push((child, delta));
+                }
+            }
+        }
+    }
+
+    fn best_goal_link(&self) -> Option<(usize, f32)> {
+        self.goal_links
+            .iter()
+            .map(|&i| (i, self.costs[i] + (self.goal - self.nodes[i]).length()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn update_path(&mut self) {
+        let Some((mut node, _)) = self.best_goal_link() else {
+            return;
+        };
+        let mut path = vec![self.goal];
+        loop {
+            path.push(self.nodes[node]);
+            match self.parents[node] {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+        path.reverse();
+        self.path = path;
+    }
+}
+
+/// A probabilistic roadmap, sampled a batch at a time and then queried for
+/// any number of start/goal pairs.
+pub struct Roadmap {
+    config: SamplingConfig,
+    rng: Rng,
+    pub nodes: Vec<Vec2>,
+    pub neighbors: Vec<Vec<usize>>,
+    // Walkability when the roadmap was built, to detect a stale roadmap
+    snapshot: Vec<bool>,
+    pub attempts: usize,
+    pub queries: usize,
+}
+
+impl Roadmap {
+    pub fn new(grid: &Grid, config: SamplingConfig) -> Self {
+        Self {
+            config,
+            rng: Rng(config.seed),
+            nodes: Vec::new(),
+            neighbors: Vec::new(),
+            snapshot: grid.walkability(),
+            attempts: 0,
+            queries: 0,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.nodes.len() < self.config.roadmap_samples
+            && self.attempts < self.config.roadmap_samples * 20
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.neighbors.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    /// True if obstacles changed since the roadmap was sampled.
+    pub fn is_stale(&self, grid: &Grid) -> bool {
+        self.snapshot != grid.walkability()
+    }
+
+    pub fn step(&mut self, grid: &Grid) {
+        for _ in 0..self.config.batch.max(1) {
+            if !self.is_running() {
+                return;
+            }
+            self.attempts += 1;
+            let sample = self.rng.point(grid);
+            if !grid.is_walkable_point(sample) {
+                continue;
+            }
+            let index = self.nodes.len();
+            let links = self.connect(grid, sample);
+            for &other in &links {
+                self.neighbors[other].push(index);
+            }
+            self.nodes.push(sample);
+            self.neighbors.push(links);
+        }
+    }
+
+    pub fn finish(&mut self, grid: &Grid) {
+        while self.is_running() {
+            self.step(grid);
+        }
+    }
+
+    /// Roadmap nodes within the connection radius visible from `point`.
+    fn connect(&self, grid: &Grid, point: Vec2) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| {
+                (self.nodes[i] - point).length() <= self.config.connection_radius
+                    && segment_is_clear(grid, point, self.nodes[i])
+            })
+            .collect()
+    }
+
+    /// Links start and goal into the roadmap and runs Dijkstra over it;
+    /// the roadmap itself is left unchanged.
+    pub fn query(&mut self, grid: &Grid, start: Position, goal: Position) -> Option<Vec<Vec2>> {
+        self.queries += 1;
+        let (start, goal) = (cell_center(&start), cell_center(&goal));
+        if segment_is_clear(grid, start, goal)
+            && (goal - start).length() <= self.config.connection_radius
+        {
+            return Some(vec![start, goal]);
+        }
+
+        // Virtual nodes: start is `n`, goal is `n + 1`
+        let n = self.nodes.len();
+        let point = |i: usize| match i {
+            i if i == n => start,
+            i if i == n + 1 => goal,
+            i => self.nodes[i],
+        };
+        let start_links = self.connect(grid, start);
+        let goal_links = self.connect(grid, goal);
+
+        let mut distance = vec![f32::INFINITY; n + 2];
+        let mut previous = vec![None; n + 2];
+        let mut open = BinaryHeap::from([Reverse((0u64, n))]);
+        distance[n] = 0.0;
+        while let Some(Reverse((d, current))) = open.pop() {
+            if current == n + 1 {
+                break;
+            }
+            if d as f32 / 1000.0 > distance[current] + 1e-3 {
+                continue;
+            }
+            let neighbors: Vec<usize> = if current == n {
+                start_links.clone()
+            } else {
+                let to_goal = goal_links.contains(&current).then_some(n + 1);
+                self.neighbors[current]
+                    .iter()
+                    .copied()
+                    .chain(to_goal)
+                    .collect()
+            };
+            for next in neighbors {
+                let d = distance[current] + (point(next) - point(current)).length();
+                if d < distance[next] {
+                    distance[next] = d;
+                    previous[next] = Some(current);
+                    open.push(Reverse(((d * 1000.0) as u64, next)));
+                }
+            }
+        }
+
+        previous[n + 1]?;
+        let mut path = vec![goal];
+        let mut current = n + 1;
+        while let Some(prev) = previous[current] {
+            path.push(point(prev));
+            current = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+fn nearest(nodes: &[Vec2], point: Vec2) -> usize {
+    (0..nodes.len())
+        .min_by(|&a, &b| {
+            (nodes[a] - point)
+                .length_sq()
+                .total_cmp(&(nodes[b] - point).length_sq())
+        })
+        .unwrap()
+}
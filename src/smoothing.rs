@@ -0,0 +1,240 @@
+use crate::{grid::Grid, position::Position};
+use egui::Vec2;
+
+/// Curve fitted through the pruned waypoints.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Smoothing {
+    None,
+    Chaikin,
+    BSpline,
+}
+
+impl Smoothing {
+    pub const ALL: [Smoothing; 3] = [Self::None, Self::Chaikin, Self::BSpline];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Chaikin => "Chaikin",
+            Self::BSpline => "B-spline",
+        }
+    }
+}
+
+/// Post-processing stages, applied in field order.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SmoothingOptions {
+    pub remove_redundant: bool,
+    pub string_pull: bool,
+    pub smoothing: Smoothing,
+    pub iterations: usize,
+}
+
+impl Default for SmoothingOptions {
+    fn default() -> Self {
+        Self {
+            remove_redundant: true,
+            string_pull: true,
+            smoothing: Smoothing::Chaikin,
+            iterations: 3,
+        }
+    }
+}
+
+/// Centre of a cell in continuous grid coordinates.
+pub fn cell_center(pos: &Position) -> Vec2 {
+    Vec2::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5)
+}
+
+/// Turns a cell path into a continuous polyline through cell centres.
+pub fn smooth_path(grid: &Grid, path: &[Position], options: &SmoothingOptions) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = path.iter().map(cell_center).collect();
+    if options.remove_redundant {
+        points = remove_redundant(&points);
+    }
+    if options.string_pull {
+        points = string_pull(grid, &points);
+    }
+    match options.smoothing {
+        Smoothing::None => points,
+        Smoothing::Chaikin => chaikin(&points, options.iterations),
+        Smoothing::BSpline => b_spline(&points, 8),
+    }
+}
+
+/// Drops points that lie on a straight line between their neighbours.
+pub fn remove_redundant(points: &[Vec2]) -> Vec<Vec2> {
+    let mut result: Vec<Vec2> = Vec::with_capacity(points.len());
+    for (i, &point) in points.iter().enumerate() {
+        if let (Some(&previous), Some(&next)) = (result.last(), points.get(i + 1)) {
+            let (a, b) = (point - previous, next - point);
+            let collinear = (a.x * b.y - a.y * b.x).abs() < 1e-4 && a.dot(b) > 0.0;
+            if point == previous || collinear {
+                continue;
+            }
+        }
+        result.push(point);
+    }
+    result
+}
+
+/// Greedy line-of-sight shortcutting: from each kept point, jump to the
+/// farthest later point that can be reached in a straight line.
+pub fn string_pull(grid: &Grid, points: &[Vec2]) -> Vec<Vec2> {
+    let Some(&first) = points.first() else {
+        return Vec::new();
+    };
+    let mut result = vec![first];
+    let mut anchor = 0;
+    while anchor < points.len() - 1 {
+        let next = (anchor + 1..points.len())
+            .rev()
+            .find(|&j| segment_is_clear(grid, points[anchor], points[j]))
+            .unwrap_or(anchor + 1);
+        result.push(points[next]);
+        anchor = next;
+    }
+    result
+}
+
+/// Chaikin corner cutting: each pass replaces every corner with two points
+/// at 1/4 and 3/4 along its edges. The endpoints stay fixed.
+pub fn chaikin(points: &[Vec2], iterations: usize) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        let mut refined = vec![points[0]];
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            refined.push(a * 0.75 + b * 0.25);
+            refined.push(a * 0.25 + b * 0.75);
+        }
+        refined.push(points[points.len() - 1]);
+        // The cuts next to the fixed endpoints are redundant
+        refined.remove(1);
+        refined.remove(refined.len() - 2);
+        points = refined;
+    }
+    points
+}
+
+/// Uniform cubic B-spline with the endpoints repeated so the curve starts
+/// and ends on them, sampled `samples` times per control-point span.
+pub fn b_spline(points: &[Vec2], samples: usize) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let control: Vec<Vec2> = [first, first]
+        .into_iter()
+        .chain(points.iter().copied())
+        .chain([last, last])
+        .collect();
+
+    let mut curve = Vec::new();
+    for span in control.windows(4) {
+        for i in 0..samples {
+            let t = i as f32 / samples as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            let b0 = (1.0 - t).powi(3) / 6.0;
+            let b1 = (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0;
+            let b2 = (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0;
+            let b3 = t3 / 6.0;
+            curve.push(span[0] * b0 + span[1] * b1 + span[2] * b2 + span[3] * b3);
+        }
+    }
+    curve.push(last);
+    curve
+}
+
+/// True if the straight segment between two points only crosses walkable
+/// cells. Walks every cell the segment touches (Amanatides–Woo) and, when
+/// it passes exactly through a corner, requires both side cells to be free.
+pub fn segment_is_clear(grid: &Grid, from: Vec2, to: Vec2) -> bool {
+    let walkable = |x: i32, y: i32| grid.is_walkable(&Position::new(x, y));
+    let (mut x, mut y) = (from.x.floor() as i32, from.y.floor() as i32);
+    let (end_x, end_y) = (to.x.floor() as i32, to.y.floor() as i32);
+    let delta = to - from;
+    let (step_x, step_y) = (delta.x.signum() as i32, delta.y.signum() as i32);
+
+    let boundary = |start: f32, d: f32, cell: i32| {
+        if d == 0.0 {
+            f32::INFINITY
+        } else {
+            let edge = if d > 0.0 { cell + 1 } else { cell } as f32;
+            (edge - start) / d
+        }
+    };
+    let mut t_max_x = boundary(from.x, delta.x, x);
+    let mut t_max_y = boundary(from.y, delta.y, y);
+    let t_delta_x = if delta.x == 0.0 {
+        f32::INFINITY
+    } else {
+        1.0 / delta.x.abs()
+    };
+    let t_delta_y = if delta.y == 0.0 {
+        f32::INFINITY
+    } else {
+        1.0 / delta.y.abs()
+    };
+
+    let max_cells = (end_x - x).abs() + (end_y - y).abs() + 1;
+    for _ in 0..=max_cells {
+        if !walkable(x, y) {
+            return false;
+        }
+        if (x, y) == (end_x, end_y) {
+            return true;
+        }
+        if (t_max_x - t_max_y).abs() < 1e-5 {
+            if !walkable(x + step_x, y) || !walkable(x, y + step_y) {
+                return false;
+            }
+            x += step_x;
+            y += step_y;
+            t_max_x += t_delta_x;
+            t_max_y += t_delta_y;
+        } else if t_max_x < t_max_y {
+            x += step_x;
+            t_max_x += t_delta_x;
+        } else {
+            y += step_y;
+            t_max_y += t_delta_y;
+        }
+    }
+    false
+}
+
+/// True if every segment of the polyline is collision-free.
+pub fn polyline_is_clear(grid: &Grid, points: &[Vec2]) -> bool {
+    points
+        .windows(2)
+        .all(|pair| segment_is_clear(grid, pair[0], pair[1]))
+}
+
+pub fn polyline_length(points: &[Vec2]) -> f32 {
+    points
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).length())
+        .sum()
+}
+
+/// Sum of absolute heading changes along the polyline, in radians.
+pub fn total_turning(points: &[Vec2]) -> f32 {
+    let headings: Vec<f32> = points
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|d| d.length_sq() > 0.0)
+        .map(|d| d.y.atan2(d.x))
+        .collect();
+    headings
+        .windows(2)
+        .map(|pair| {
+            let turn = (pair[1] - pair[0]).rem_euclid(std::f32::consts::TAU);
+            turn.min(std::f32::consts::TAU - turn)
+        })
+        .sum()
+}
@@ -46,6 +46,22 @@ impl Default for Theme {
 }
 
 impl Theme {
+    /// Maps `t` in `0.0..=1.0` onto a cool-to-warm gradient used by heatmap overlays.
+    pub fn gradient(t: f32) -> Color32 {
+        const STOPS: [Color32; 4] = [
+            Color32::from_rgb(49, 54, 149),
+            Color32::from_rgb(116, 173, 209),
+            Color32::from_rgb(254, 224, 144),
+            Color32::from_rgb(215, 48, 39),
+        ];
+        let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+        let index = (scaled.floor() as usize).min(STOPS.len() - 2);
+        let local = scaled - index as f32;
+        let (a, b) = (STOPS[index], STOPS[index + 1]);
+        let lerp = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * local).round() as u8;
+        Color32::from_rgb(lerp(a.r(), b.r()), lerp(a.g(), b.g()), lerp(a.b(), b.b()))
+    }
+
     pub fn style(&self) -> egui::Style {
         let mut style = egui::Style {
             visuals: egui::Visuals::light(),
@@ -4,4 +4,12 @@ pub enum Tool {
     SetGoal,
     AddObstacle,
     RemoveObstacle,
+    SelectArea,
+    AddWaypoint,
+    PlaceAgent,
+    AddMovingObstacle,
+    SpawnFlowAgent,
+    PlaceLandmark,
+    PlaceStairs,
+    PlaceElevator,
 }
@@ -0,0 +1,135 @@
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Shape of the cells and which cells count as neighbours. Hex grids use
+/// axial coordinates (x = q, y = r), so the stored rectangle of cells is a
+/// parallelogram on screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Square,
+    PointyHex,
+    FlatHex,
+}
+
+const SQRT_3_2: f32 = 0.866_025_4;
+
+impl Topology {
+    pub const ALL: [Topology; 3] = [Self::Square, Self::PointyHex, Self::FlatHex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Square => "Square",
+            Self::PointyHex => "Hex (pointy top)",
+            Self::FlatHex => "Hex (flat top)",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Square => "Square cells with four neighbours.",
+            Self::PointyHex => {
+                "Hexagonal cells with a corner on top, six neighbours each. Rows are offset by half a cell, so the map leans to the right."
+            }
+            Self::FlatHex => {
+                "Hexagonal cells with an edge on top, six neighbours each. Columns are offset by half a cell, so the map leans downwards."
+            }
+        }
+    }
+
+    pub fn is_hex(&self) -> bool {
+        *self != Self::Square
+    }
+
+    /// Offsets to the neighbouring cells. Both hex orientations share the
+    /// same axial neighbours; only the drawing differs.
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Self::Square => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Self::PointyHex | Self::FlatHex => {
+                &[(1, -1), (1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1)]
+            }
+        }
+    }
+
+    /// Number of steps between two cells on an empty grid.
+    pub fn distance(&self, dx: i32, dy: i32) -> i32 {
+        match self {
+            Self::Square => dx.abs() + dy.abs(),
+            Self::PointyHex | Self::FlatHex => (dx.abs() + dy.abs() + (dx + dy).abs()) / 2,
+        }
+    }
+
+    /// True if the offset lies within `radius`: a Euclidean disc on squares,
+    /// a hexagon of `radius` steps on hex grids.
+    pub fn within(&self, dx: i32, dy: i32, radius: f32) -> bool {
+        match self {
+            Self::Square => ((dx * dx + dy * dy) as f32) <= radius * radius,
+            Self::PointyHex | Self::FlatHex => self.distance(dx, dy) as f32 <= radius,
+        }
+    }
+
+    /// Maps continuous grid coordinates to layout coordinates in which
+    /// neighbouring cell centres are one unit apart.
+    pub fn layout_of(&self, point: Vec2) -> Vec2 {
+        match self {
+            Self::Square => point,
+            Self::PointyHex => Vec2::new(point.x + point.y * 0.5, point.y * SQRT_3_2),
+            Self::FlatHex => Vec2::new(point.x * SQRT_3_2, point.y + point.x * 0.5),
+        }
+    }
+
+    pub fn grid_of(&self, point: Vec2) -> Vec2 {
+        match self {
+            Self::Square => point,
+            Self::PointyHex => {
+                let y = point.y / SQRT_3_2;
+                Vec2::new(point.x - y * 0.5, y)
+            }
+            Self::FlatHex => {
+                let x = point.x / SQRT_3_2;
+                Vec2::new(x, point.y - x * 0.5)
+            }
+        }
+    }
+
+    /// Cell containing a continuous grid coordinate. Hex cells are centred
+    /// on the coordinates at +0.5, so the point is rounded to the nearest
+    /// centre in cube coordinates.
+    pub fn cell_at(&self, point: Vec2) -> (i32, i32) {
+        if !self.is_hex() {
+            return (point.x.floor() as i32, point.y.floor() as i32);
+        }
+        let (q, r) = (point.x - 0.5, point.y - 0.5);
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        (rq as i32, rr as i32)
+    }
+
+    /// Outline of a cell around the origin, in layout units.
+    pub fn corners(&self) -> Vec<Vec2> {
+        // Circumradius of a hexagon whose opposite edges are one unit apart
+        let radius = 1.0 / (2.0 * SQRT_3_2);
+        let first_angle = match self {
+            Self::Square => {
+                return vec![
+                    Vec2::new(-0.5, -0.5),
+                    Vec2::new(0.5, -0.5),
+                    Vec2::new(0.5, 0.5),
+                    Vec2::new(-0.5, 0.5),
+                ];
+            }
+            Self::PointyHex => 30.0f32,
+            Self::FlatHex => 0.0,
+        };
+        (0..6)
+            .map(|i| Vec2::angled((first_angle + 60.0 * i as f32).to_radians()) * radius)
+            .collect()
+    }
+}
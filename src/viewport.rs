@@ -0,0 +1,104 @@
+use crate::{position::Position, topology::Topology};
+use egui::{Pos2, Rect, Vec2};
+
+pub const MIN_CELL_SIZE: f32 = 2.0;
+pub const MAX_CELL_SIZE: f32 = 80.0;
+
+/// Maps grid coordinates to screen space for one frame of `render_grid`.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub origin: Pos2,
+    // Distance between neighbouring cell centres
+    pub cell_size: f32,
+    pub topology: Topology,
+    // Floor whose cells are shown; clicked cells land on it
+    pub floor: i32,
+}
+
+impl Viewport {
+    pub fn new(origin: Pos2, cell_size: f32, topology: Topology) -> Self {
+        Self {
+            origin,
+            cell_size,
+            topology,
+            floor: 0,
+        }
+    }
+
+    pub fn on_floor(self, floor: i32) -> Self {
+        Self { floor, ..self }
+    }
+
+    /// The cell at `x`, `y` on the shown floor.
+    pub fn cell(&self, x: i32, y: i32) -> Position {
+        Position::on_floor(x, y, self.floor)
+    }
+
+    /// The cell's bounding square; for hexagons, the square of side
+    /// `cell_size` around the centre.
+    pub fn cell_rect(&self, pos: &Position) -> Rect {
+        Rect::from_center_size(self.cell_center(pos), Vec2::splat(self.cell_size))
+    }
+
+    pub fn cell_center(&self, pos: &Position) -> Pos2 {
+        self.grid_to_screen(Vec2::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5))
+    }
+
+    /// Screen-space outline of the cell.
+    pub fn cell_polygon(&self, pos: &Position) -> Vec<Pos2> {
+        let center = self.cell_center(pos);
+        self.topology
+            .corners()
+            .into_iter()
+            .map(|corner| center + corner * self.cell_size)
+            .collect()
+    }
+
+    /// Converts a continuous grid coordinate (1.0 = one cell) to screen space.
+    pub fn grid_to_screen(&self, point: Vec2) -> Pos2 {
+        self.origin + self.topology.layout_of(point) * self.cell_size
+    }
+
+    pub fn screen_to_grid(&self, point: Pos2) -> Vec2 {
+        self.topology
+            .grid_of((point - self.origin) / self.cell_size)
+    }
+
+    pub fn screen_to_cell(&self, point: Pos2) -> Position {
+        let (x, y) = self.topology.cell_at(self.screen_to_grid(point));
+        self.cell(x, y)
+    }
+
+    /// Range of cells overlapping `clip`, clamped to the grid bounds.
+    pub fn visible_cells(
+        &self,
+        clip: Rect,
+        width: usize,
+        height: usize,
+    ) -> (std::ops::Range<i32>, std::ops::Range<i32>) {
+        // On hex grids the clip rect is a sheared area in grid space, so take
+        // the bounds of all four corners plus a margin for the cell outlines
+        let corners = [
+            clip.left_top(),
+            clip.right_top(),
+            clip.left_bottom(),
+            clip.right_bottom(),
+        ]
+        .map(|corner| self.screen_to_grid(corner));
+        let margin = if self.topology.is_hex() { 1.0 } else { 0.0 };
+        let min = corners.iter().fold(Vec2::INFINITY, |a, b| a.min(*b)) - Vec2::splat(margin);
+        let max = corners.iter().fold(-Vec2::INFINITY, |a, b| a.max(*b)) + Vec2::splat(margin);
+        let x0 = (min.x.floor() as i32).clamp(0, width as i32);
+        let y0 = (min.y.floor() as i32).clamp(0, height as i32);
+        let x1 = (max.x.ceil() as i32).clamp(0, width as i32);
+        let y1 = (max.y.ceil() as i32).clamp(0, height as i32);
+        (x0..x1, y0..y1)
+    }
+
+    /// Returns the origin that keeps the grid point under `anchor` fixed
+    /// when the cell size changes to `new_cell_size`.
+    pub fn zoomed_origin(&self, anchor: Pos2, new_cell_size: f32) -> Pos2 {
+        let grid_point = self.screen_to_grid(anchor);
+        anchor - self.topology.layout_of(grid_point) * new_cell_size
+    }
+}
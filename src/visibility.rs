@@ -0,0 +1,210 @@
+use crate::{
+    grid::Grid,
+    position::Position,
+    smoothing::{cell_center, segment_is_clear},
+};
+use egui::Vec2;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// How far corner nodes are pushed off the lattice point into free space,
+/// so segments ending there are tested against the right cells.
+const CORNER_OFFSET: f32 = 1e-3;
+
+/// Visibility graph over the convex corners of the obstacle polygons plus
+/// start and goal, with its Euclidean shortest path.
+pub struct VisibilityGraph {
+    // Obstacle outlines in grid coordinates (cell corners)
+    pub polygons: Vec<Vec<Vec2>>,
+    // Corner nodes, then start and goal as the last two
+    pub nodes: Vec<Vec2>,
+    pub edges: Vec<(usize, usize)>,
+    pub path: Option<Vec<Vec2>>,
+}
+
+impl VisibilityGraph {
+    pub fn build(grid: &Grid, start: Position, goal: Position) -> Self {
+        let polygons = trace_polygons(grid);
+        let mut nodes: Vec<Vec2> = polygons
+            .iter()
+            .flat_map(|polygon| convex_corners(polygon))
+            .filter(|p| grid.is_walkable_point(*p))
+            .collect();
+        nodes.push(cell_center(&start));
+        nodes.push(cell_center(&goal));
+
+        let mut edges = Vec::new();
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                if segment_is_clear(grid, nodes[i], nodes[j]) {
+                    edges.push((i, j));
+                }
+            }
+        }
+
+        let path = shortest_path(&nodes, &edges, nodes.len() - 2, nodes.len() - 1);
+        Self {
+            polygons: polygons
+                .into_iter()
+                .map(|polygon| {
+                    polygon
+                        .into_iter()
+                        .map(|(x, y)| Vec2::new(x as f32, y as f32))
+                        .collect()
+                })
+                .collect(),
+            nodes,
+            edges,
+            path,
+        }
+    }
+
+    pub fn corner_count(&self) -> usize {
+        self.nodes.len() - 2
+    }
+
+    pub fn path_length(&self) -> Option<f32> {
+        self.path.as_deref().map(crate::smoothing::polyline_length)
+    }
+}
+
+/// Traces the outline of every group of blocked cells. Boundary edges run
+/// along the sides of blocked cells that face free cells, oriented with the
+/// obstacle on the right, and are chained into closed loops; at a vertex
+/// where two obstacles touch diagonally the tightest right turn is taken,
+/// so such obstacles stay separate. Collinear vertices are dropped.
+fn trace_polygons(grid: &Grid) -> Vec<Vec<(i32, i32)>> {
+    let blocked = |x: i32, y: i32| {
+        let pos = Position::new(x, y);
+        grid.is_valid_position(&pos) && !grid.is_walkable(&pos)
+    };
+
+    // Outgoing boundary edges per lattice vertex, as direction vectors
+    let mut outgoing: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+    for y in 0..grid.height as i32 {
+        for x in 0..grid.width as i32 {
+            if !blocked(x, y) {
+                continue;
+            }
+            let sides = [
+                (!blocked(x, y - 1), (x, y), (1, 0)),
+                (!blocked(x + 1, y), (x + 1, y), (0, 1)),
+                (!blocked(x, y + 1), (x + 1, y + 1), (-1, 0)),
+                (!blocked(x - 1, y), (x, y + 1), (0, -1)),
+            ];
+            for (open, from, direction) in sides {
+                if open {
+                    outgoing.entry(from).or_default().push(direction);
+                }
+            }
+        }
+    }
+
+    let mut polygons = Vec::new();
+    let mut starts: Vec<(i32, i32)> = outgoing.keys().copied().collect();
+    starts.sort_by_key(|&(x, y)| (y, x));
+    for start in starts {
+        while let Some(first) = outgoing.get_mut(&start).and_then(Vec::pop) {
+            let mut loop_vertices = vec![start];
+            let mut vertex = (start.0 + first.0, start.1 + first.1);
+            let mut direction = first;
+            while vertex != start {
+                loop_vertices.push(vertex);
+                let options = outgoing.get_mut(&vertex).unwrap();
+                // Right turn first, then straight, then left
+                let right = (-direction.1, direction.0);
+                let left = (direction.1, -direction.0);
+                let index = [right, direction, left]
+                    .iter()
+                    .find_map(|d| options.iter().position(|o| o == d))
+                    .unwrap();
+                direction = options.swap_remove(index);
+                vertex = (vertex.0 + direction.0, vertex.1 + direction.1);
+            }
+            polygons.push(drop_collinear(&loop_vertices));
+        }
+    }
+    polygons
+}
+
+fn drop_collinear(vertices: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let n = vertices.len();
+    (0..n)
+        .filter(|&i| {
+            let (a, b, c) = (
+                vertices[(i + n - 1) % n],
+                vertices[i],
+                vertices[(i + 1) % n],
+            );
+            (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0) != 0
+        })
+        .map(|i| vertices[i])
+        .collect()
+}
+
+/// Corners where the outline turns right, i.e. around the obstacle, pushed
+/// slightly outwards along the corner's diagonal.
+fn convex_corners(polygon: &[(i32, i32)]) -> Vec<Vec2> {
+    let n = polygon.len();
+    let direction = |a: (i32, i32), b: (i32, i32)| {
+        Vec2::new((b.0 - a.0).signum() as f32, (b.1 - a.1).signum() as f32)
+    };
+    (0..n)
+        .filter_map(|i| {
+            let (a, b, c) = (polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]);
+            let (incoming, outgoing) = (direction(a, b), direction(b, c));
+            let right_turn = incoming.x * outgoing.y - incoming.y * outgoing.x > 0.0;
+            right_turn
+                .then(|| Vec2::new(b.0 as f32, b.1 as f32) + (incoming - outgoing) * CORNER_OFFSET)
+        })
+        .collect()
+}
+
+/// Dijkstra over the visibility graph with Euclidean edge lengths.
+fn shortest_path(
+    nodes: &[Vec2],
+    edges: &[(usize, usize)],
+    start: usize,
+    goal: usize,
+) -> Option<Vec<Vec2>> {
+    let mut adjacency = vec![Vec::new(); nodes.len()];
+    for &(a, b) in edges {
+        let length = (nodes[b] - nodes[a]).length();
+        adjacency[a].push((b, length));
+        adjacency[b].push((a, length));
+    }
+
+    let mut distance = vec![f32::INFINITY; nodes.len()];
+    let mut previous = vec![None; nodes.len()];
+    distance[start] = 0.0;
+    // Distances are non-negative, so their bit patterns sort like the floats
+    let mut open = BinaryHeap::from([Reverse((0.0f32.to_bits(), start))]);
+    while let Some(Reverse((bits, node))) = open.pop() {
+        if node == goal {
+            break;
+        }
+        if f32::from_bits(bits) > distance[node] {
+            continue;
+        }
+        for &(next, length) in &adjacency[node] {
+            let d = distance[node] + length;
+            if d < distance[next] {
+                distance[next] = d;
+                previous[next] = Some(node);
+                open.push(Reverse((d.to_bits(), next)));
+            }
+        }
+    }
+
+    if !distance[goal].is_finite() {
+        return None;
+    }
+    let mut path = vec![nodes[goal]];
+    let mut current = goal;
+    while let Some(prev) = previous[current] {
+        path.push(nodes[prev]);
+        current = prev;
+    }
+    path.reverse();
+    Some(path)
+}